@@ -3,162 +3,202 @@ use std::{
     process::Command,
 };
 
+use shader_compiler::{naga, EntryPoint, ShaderSource, ShaderStage};
+
+const SHADER_DIR: &str = "data/shaders";
+const OUTPUT_DIR: &str = "data/generated/shaders";
+
 fn main() {
     // Ensure folders
-    let _ = std::fs::create_dir_all("data/generated/shaders");
+    let _ = std::fs::create_dir_all(OUTPUT_DIR);
 
-    // Compile shaders
-    let input_path = "data/shaders/shader.wgsl";
-    println!("cargo::rerun-if-changed={}", input_path);
-    let vertex_shader = Shader::new(
-        input_path,
-        "data/generated/shaders/vertex.spv",
-        "vs_main".to_owned(),
-        ShaderStage::Vertex,
-    );
-    let fragment_shader = Shader::new(
-        input_path,
-        "data/generated/shaders/fragment.spv",
-        "fs_main".to_owned(),
-        ShaderStage::Fragment,
-    );
+    // Compile every entry point of every shader, and generate a module per
+    // file with a constant per entry point that embeds the results
+    println!("cargo::rerun-if-changed={}", SHADER_DIR);
+    let mut generated = String::from("// Generated by build.rs, do not edit.\n");
+    for wgsl_path in wgsl_files(SHADER_DIR) {
+        let source = ShaderSource::read(&wgsl_path).unwrap_or_else(|error| fail(&error));
+        for file in &source.files {
+            println!("cargo::rerun-if-changed={}", file.display());
+        }
+        let module = source.parse().unwrap_or_else(|error| fail(&error));
+        source
+            .validate(&module)
+            .unwrap_or_else(|error| fail(&error));
+        let name = identifier(&wgsl_path.file_stem().unwrap().to_string_lossy());
+        generated += &format!("\npub mod {} {{\n", name);
+        for entry_point in &module.entry_points {
+            let stage = match entry_point.stage {
+                naga::ShaderStage::Vertex => ShaderStage::Vertex,
+                naga::ShaderStage::Fragment => ShaderStage::Fragment,
+                naga::ShaderStage::Compute => {
+                    println!(
+                        "cargo::warning=Skipping compute entry point {} in {}",
+                        entry_point.name,
+                        wgsl_path.display()
+                    );
+                    continue;
+                }
+            };
+            let shader = Shader::new(
+                &source,
+                format!("{}/{}_{}", OUTPUT_DIR, name, entry_point.name),
+                entry_point.name.clone(),
+                stage,
+            );
+            generated += &shader.compile(module.clone());
+        }
+        generated += "}\n";
+    }
+    let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").unwrap());
+    std::fs::write(out_dir.join("shaders.rs"), generated).unwrap();
+}
 
-    let backend = if cfg!(feature = "vulkan") {
-        Backend::Vulkan
-    } else if cfg!(feature = "dx12") {
-        Backend::DX12
-    } else if cfg!(feature = "metal") {
-        Backend::Metal
-    } else {
-        Backend::Vulkan
-    };
+/// Reports an error through cargo, rather than as a panic with a backtrace,
+/// and stops the build.
+fn fail(message: &str) -> ! {
+    for line in message.trim_end().lines() {
+        println!("cargo::error={}", line);
+    }
+    std::process::exit(1)
+}
 
-    vertex_shader.compile_for_backend(backend);
-    fragment_shader.compile_for_backend(backend);
+/// The WGSL files in `dir`, in a stable order.
+fn wgsl_files<P: AsRef<Path>>(dir: P) -> Vec<PathBuf> {
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "wgsl")
+        })
+        .collect();
+    paths.sort();
+    paths
 }
 
-struct Shader {
-    wgsl_path: PathBuf,
-    spv_path: PathBuf,
+/// Turns a file name into something usable as a Rust module name.
+fn identifier(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else {
+        name
+    }
+}
+
+struct Shader<'a> {
+    source: &'a ShaderSource,
+    /// The path of the compiled shader, minus the extension, which depends
+    /// on the format.
+    output_path: PathBuf,
     entrypoint: String,
     stage: ShaderStage,
 }
 
-impl Shader {
-    fn new<P1: AsRef<Path>, P2: AsRef<Path>>(
-        wgsl_path: P1,
-        output_path: P2,
+impl<'a> Shader<'a> {
+    fn new<P: AsRef<Path>>(
+        source: &'a ShaderSource,
+        output_path: P,
         entrypoint: String,
         stage: ShaderStage,
     ) -> Self {
-        let wgsl_path = wgsl_path.as_ref().to_owned();
-        let spv_path = output_path.as_ref().to_owned();
+        let output_path = output_path.as_ref().to_owned();
         Self {
-            wgsl_path,
-            spv_path,
+            source,
+            output_path,
             entrypoint,
             stage,
         }
     }
 
-    fn compile_to_spv(&self) {
-        compile_shader(
-            &self.wgsl_path,
-            &self.spv_path,
-            self.entrypoint.clone(),
-            self.stage,
-        );
-    }
+    /// Writes SPIR-V, MSL and HLSL for the entry point with naga, plus DXIL
+    /// if shadercross is installed. Returns the Rust code that embeds them.
+    fn compile(&self, module: naga::Module) -> String {
+        let entry_point = EntryPoint::new(self.source, module, &self.entrypoint, self.stage)
+            .unwrap_or_else(|error| fail(&error));
+        let spv = entry_point.to_spv().unwrap_or_else(|error| fail(&error));
+        std::fs::write(self.output_path.with_extension("spv"), spv).unwrap();
+        let msl = entry_point.to_msl().unwrap_or_else(|error| fail(&error));
+        std::fs::write(self.output_path.with_extension("msl"), msl).unwrap();
+        let hlsl = entry_point.to_hlsl().unwrap_or_else(|error| fail(&error));
+        let hlsl_path = self.output_path.with_extension("hlsl");
+        std::fs::write(&hlsl_path, hlsl).unwrap();
 
-    fn compile_for_backend(&self, backend: Backend) {
-        self.compile_to_spv();
-        if backend == Backend::Vulkan {
-            return;
+        let mut formats = vec![
+            ("SDL_GPU_SHADERFORMAT_SPIRV", "spv"),
+            ("SDL_GPU_SHADERFORMAT_MSL", "msl"),
+        ];
+        if run_shadercross(&hlsl_path, &self.entrypoint, self.stage) {
+            formats.push(("SDL_GPU_SHADERFORMAT_DXIL", "dxil"));
         }
-        run_shadercross(&self.spv_path, &self.entrypoint, self.stage, backend);
+        self.embedding(&entry_point.resources, &formats)
     }
-}
-
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-enum Backend {
-    Vulkan,
-    DX12,
-    Metal,
-}
 
-#[derive(Copy, Clone)]
-enum ShaderStage {
-    Vertex,
-    Fragment,
-}
-
-impl From<ShaderStage> for naga::ShaderStage {
-    fn from(value: ShaderStage) -> Self {
-        match value {
-            ShaderStage::Vertex => Self::Vertex,
-            ShaderStage::Fragment => Self::Fragment,
-        }
+    /// A constant, named after the entry point, that embeds each compiled
+    /// format along with the entry point's resource counts.
+    fn embedding(
+        &self,
+        resources: &shader_compiler::ShaderResources,
+        formats: &[(&str, &str)],
+    ) -> String {
+        let stage = match self.stage {
+            ShaderStage::Vertex => "SDL_GPU_SHADERSTAGE_VERTEX",
+            ShaderStage::Fragment => "SDL_GPU_SHADERSTAGE_FRAGMENT",
+        };
+        let code = formats
+            .iter()
+            .map(|(format, extension)| {
+                format!(
+                    "            (sdl3_sys::gpu::{}, include_bytes!(concat!(env!(\"CARGO_MANIFEST_DIR\"), \"/{}\"))),\n",
+                    format,
+                    self.output_path.with_extension(extension).display()
+                )
+            })
+            .collect::<String>();
+        format!(
+            "    pub const {}: crate::shader::EmbeddedShader = crate::shader::EmbeddedShader {{
+        source: \"{}\",
+        metadata: crate::shader::ShaderMetadata {{
+            entrypoint: c\"{}\",
+            stage: sdl3_sys::gpu::{},
+            num_samplers: {},
+            num_storage_textures: {},
+            num_storage_buffers: {},
+            num_uniform_buffers: {},
+        }},
+        code: &[
+{}        ],
+    }};
+",
+            self.entrypoint.to_uppercase(),
+            self.source.path.display().to_string().replace('\\', "/"),
+            self.entrypoint,
+            stage,
+            resources.num_samplers,
+            resources.num_storage_textures,
+            resources.num_storage_buffers,
+            resources.num_uniform_buffers,
+            code,
+        )
     }
 }
 
-fn read_and_validate_shader<P: AsRef<Path>>(
-    input_path: P,
-) -> (naga::Module, naga::valid::ModuleInfo) {
-    let input_text = std::fs::read_to_string(input_path).unwrap();
-    let module = naga::front::wgsl::parse_str(&input_text).unwrap();
-    let info = {
-        let mut validator = naga::valid::Validator::new(
-            naga::valid::ValidationFlags::all(),
-            naga::valid::Capabilities::all(),
-        );
-        validator.validate(&module).unwrap()
-    };
-    (module, info)
-}
-
-fn compile_shader<P1: AsRef<Path>, P2: AsRef<Path>>(
-    input_path: P1,
-    output_path: P2,
-    entrypoint: String,
-    stage: ShaderStage,
-) {
-    let (module, info) = read_and_validate_shader(input_path);
-    let pipeline_options = naga::back::spv::PipelineOptions {
-        shader_stage: stage.into(),
-        entry_point: entrypoint,
-    };
-    let options = naga::back::spv::Options {
-        ..Default::default()
-    };
-    let spv =
-        naga::back::spv::write_vec(&module, &info, &options, Some(&pipeline_options)).unwrap();
-    let bytes = spv
-        .iter()
-        .fold(Vec::with_capacity(spv.len() * 4), |mut v, w| {
-            v.extend_from_slice(&w.to_le_bytes());
-            v
-        });
-    std::fs::write(output_path, &bytes).unwrap()
-}
-
-fn run_shadercross<P: AsRef<Path>>(
-    input_path: P,
-    entrypoint: &str,
-    stage: ShaderStage,
-    backend: Backend,
-) {
+/// Compiles HLSL to DXIL. naga can't do this part, so unlike the other
+/// formats it needs shadercross (and the dxc it uses) to be installed.
+/// Returns false if it isn't, in which case D3D12 won't be available.
+fn run_shadercross<P: AsRef<Path>>(input_path: P, entrypoint: &str, stage: ShaderStage) -> bool {
     let input_path = input_path.as_ref();
-    let extension = match backend {
-        Backend::Vulkan => "spv",
-        Backend::DX12 => "dxil",
-        Backend::Metal => "msl",
-    };
-    let output_path = input_path.with_extension(extension);
-    let target = match backend {
-        Backend::Vulkan => "SPIRV",
-        Backend::DX12 => "DXIL",
-        Backend::Metal => "MSL",
-    };
+    let output_path = input_path.with_extension("dxil");
     let stage = match stage {
         ShaderStage::Vertex => "vertex",
         ShaderStage::Fragment => "fragment",
@@ -167,9 +207,9 @@ fn run_shadercross<P: AsRef<Path>>(
     command.args([
         input_path.to_str().unwrap(),
         "-s",
-        "SPIRV",
+        "HLSL",
         "-d",
-        target,
+        "DXIL",
         "-e",
         entrypoint,
         "-o",
@@ -179,6 +219,22 @@ fn run_shadercross<P: AsRef<Path>>(
     ]);
     // This will fail if you have the version of dxc from the Windows SDK
     // on your PATH. Grab the latest from GitHub and make sure it comes first.
-    let status = command.status().unwrap();
-    assert!(status.success());
+    let status = match command.status() {
+        Ok(status) => status,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+            println!(
+                "cargo::warning=shadercross wasn't found, skipping DXIL for {}",
+                input_path.display()
+            );
+            return false;
+        }
+        Err(error) => fail(&format!("Couldn't run shadercross: {}", error)),
+    };
+    if !status.success() {
+        fail(&format!(
+            "shadercross failed to compile {}",
+            input_path.display()
+        ));
+    }
+    true
 }
@@ -0,0 +1,24 @@
+struct VertexOutput {
+    float4 position : SV_Position;
+    float4 color : TEXCOORD0;
+};
+
+struct Globals {
+    row_major float4x4 transform;
+};
+
+struct Locals {
+    row_major float4x4 transform;
+};
+
+
+struct FragmentInput_fs_main {
+    float4 color : TEXCOORD0;
+    float4 position : SV_Position;
+};
+
+float4 fs_main(FragmentInput_fs_main fragmentinput_fs_main) : SV_Target0
+{
+    VertexOutput in_ = { fragmentinput_fs_main.position, fragmentinput_fs_main.color };
+    return in_.color;
+}
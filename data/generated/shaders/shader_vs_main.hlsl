@@ -0,0 +1,40 @@
+struct VertexOutput {
+    float4 position : SV_Position;
+    float4 color : TEXCOORD0;
+};
+
+struct Globals {
+    row_major float4x4 transform;
+};
+
+struct Locals {
+    row_major float4x4 transform;
+};
+
+cbuffer r_globals : register(b0, space1) { Globals r_globals; }
+cbuffer r_locals : register(b1, space1) { Locals r_locals; }
+
+struct VertexOutput_vs_main {
+    float4 color_1 : TEXCOORD0;
+    float4 position_1 : SV_Position;
+};
+
+VertexOutput_vs_main vs_main(float4 position : TEXCOORD0, float4 color : TEXCOORD1)
+{
+    float4 in_position = (float4)0;
+    VertexOutput out_ = (VertexOutput)0;
+
+    in_position.x = position.x;
+    in_position.y = position.y;
+    in_position.z = position.z;
+    in_position.w = 1.0;
+    out_.color = color;
+    float4x4 _e16 = r_globals.transform;
+    float4x4 _e19 = r_locals.transform;
+    float4 _e21 = in_position;
+    out_.position = mul(_e21, mul(_e19, _e16));
+    VertexOutput _e23 = out_;
+    const VertexOutput vertexoutput = _e23;
+    const VertexOutput_vs_main vertexoutput_1 = { vertexoutput.color, vertexoutput.position };
+    return vertexoutput_1;
+}
@@ -0,0 +1,12 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+struct Globals {
+    transform: mat4x4<f32>,
+};
+
+struct Locals {
+    transform: mat4x4<f32>,
+};
@@ -1,18 +1,9 @@
-struct VertexOutput {
-    @builtin(position) position: vec4<f32>,
-    @location(0) color: vec4<f32>,
-};
+#include "include/common.wgsl"
 
-struct Globals {
-    transform: mat4x4<f32>,
-};
 @group(1)
 @binding(0)
 var<uniform> r_globals: Globals;
 
-struct Locals {
-    transform: mat4x4<f32>,
-};
 @group(1)
 @binding(1)
 var<uniform> r_locals: Locals;
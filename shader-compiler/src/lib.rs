@@ -0,0 +1,1021 @@
+//! Compiles WGSL into the shader formats SDL takes, using naga. sdl3cube's
+//! build.rs uses this to compile data/shaders ahead of time, and its debug
+//! builds use it again to reload them while running.
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+pub use naga;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ShaderStage {
+    Vertex,
+    Fragment,
+}
+
+impl ShaderStage {
+    /// SDL expects a stage's textures and storage buffers in one bind group
+    /// and its uniform buffers in the next. Returns (resources, uniforms).
+    fn bind_groups(self) -> (u32, u32) {
+        match self {
+            ShaderStage::Vertex => (0, 1),
+            ShaderStage::Fragment => (2, 3),
+        }
+    }
+}
+
+impl From<ShaderStage> for naga::ShaderStage {
+    fn from(value: ShaderStage) -> Self {
+        match value {
+            ShaderStage::Vertex => Self::Vertex,
+            ShaderStage::Fragment => Self::Fragment,
+        }
+    }
+}
+
+/// A WGSL file after preprocessing, kept around so that errors can quote it.
+///
+/// Before naga sees the text, lines starting with `#` are handled as:
+///   * `#include "file.wgsl"`, which pastes in a file relative to the one
+///     including it. Each file is only included once, so shared files don't
+///     need include guards.
+///   * `#define NAME`, `#ifdef NAME`, `#ifndef NAME`, `#else` and `#endif`,
+///     for turning features on and off.
+pub struct ShaderSource {
+    pub path: PathBuf,
+    /// The preprocessed text that naga sees.
+    text: String,
+    /// Every file that went into `text`, starting with `path`.
+    pub files: Vec<PathBuf>,
+    /// The file, as an index into `files`, and line number that each line
+    /// of `text` came from.
+    lines: Vec<(usize, usize)>,
+}
+
+/// An `#ifdef` or `#ifndef` block that's still open.
+struct Condition {
+    active: bool,
+    seen_else: bool,
+}
+
+impl ShaderSource {
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref().to_owned();
+        let text = std::fs::read_to_string(&path)
+            .map_err(|error| format!("Couldn't read {}: {}", path.display(), error))?;
+        let mut source = Self {
+            path: path.clone(),
+            text: String::new(),
+            files: Vec::new(),
+            lines: Vec::new(),
+        };
+        let canonical_path = path
+            .canonicalize()
+            .map_err(|error| format!("Couldn't read {}: {}", path.display(), error))?;
+        source.preprocess(
+            &path,
+            &text,
+            &mut HashSet::new(),
+            &mut HashSet::from([canonical_path]),
+        )?;
+        Ok(source)
+    }
+
+    fn preprocess(
+        &mut self,
+        path: &Path,
+        text: &str,
+        defines: &mut HashSet<String>,
+        included: &mut HashSet<PathBuf>,
+    ) -> Result<(), String> {
+        let file = self.files.len();
+        self.files.push(path.to_owned());
+
+        let mut conditions: Vec<Condition> = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let error =
+                |message: String| format!("{}:{}: {}", path.display(), line_number, message);
+            let active = conditions.iter().all(|condition| condition.active);
+
+            let Some(directive) = line.trim().strip_prefix('#') else {
+                self.push_line(if active { line } else { "" }, file, line_number);
+                continue;
+            };
+            let (name, argument) = directive
+                .split_once(char::is_whitespace)
+                .map_or((directive, ""), |(name, argument)| (name, argument.trim()));
+            let flag = || {
+                if argument.is_empty() || argument.contains(char::is_whitespace) {
+                    Err(error(format!("#{} takes a single name", name)))
+                } else {
+                    Ok(argument.to_owned())
+                }
+            };
+            match name {
+                "ifdef" | "ifndef" => {
+                    let defined = defines.contains(&flag()?);
+                    conditions.push(Condition {
+                        active: defined == (name == "ifdef"),
+                        seen_else: false,
+                    });
+                }
+                "else" => {
+                    let condition = conditions
+                        .last_mut()
+                        .filter(|condition| !condition.seen_else)
+                        .ok_or_else(|| error("#else without a matching #ifdef".to_owned()))?;
+                    condition.active = !condition.active;
+                    condition.seen_else = true;
+                }
+                "endif" => {
+                    conditions
+                        .pop()
+                        .ok_or_else(|| error("#endif without a matching #ifdef".to_owned()))?;
+                }
+                "define" => {
+                    let flag = flag()?;
+                    if active {
+                        defines.insert(flag);
+                    }
+                }
+                "include" => {
+                    let included_file = argument
+                        .strip_prefix('"')
+                        .and_then(|argument| argument.strip_suffix('"'))
+                        .ok_or_else(|| error("expected #include \"file\"".to_owned()))?;
+                    let included_path = path.parent().unwrap().join(included_file);
+                    if active {
+                        let read_error = |io_error: std::io::Error| {
+                            error(format!(
+                                "couldn't read {}: {}",
+                                included_path.display(),
+                                io_error
+                            ))
+                        };
+                        // Compare canonical paths, so that "./common.wgsl" or
+                        // "include/../common.wgsl" count as "common.wgsl"
+                        let canonical_path = included_path.canonicalize().map_err(read_error)?;
+                        if included.insert(canonical_path) {
+                            let included_text =
+                                std::fs::read_to_string(&included_path).map_err(read_error)?;
+                            self.preprocess(&included_path, &included_text, defines, included)?;
+                            continue;
+                        }
+                    }
+                }
+                _ => return Err(error(format!("unknown directive #{}", name))),
+            }
+            // Keep a blank line in place of the directive, so that lines
+            // only move around when something is included
+            self.push_line("", file, line_number);
+        }
+        if !conditions.is_empty() {
+            return Err(format!("{}: #ifdef without an #endif", path.display()));
+        }
+        Ok(())
+    }
+
+    fn push_line(&mut self, line: &str, file: usize, line_number: usize) {
+        self.text.push_str(line);
+        self.text.push('\n');
+        self.lines.push((file, line_number));
+    }
+
+    pub fn parse(&self) -> Result<naga::Module, String> {
+        naga::front::wgsl::parse_str(&self.text).map_err(|error| {
+            self.remap_diagnostic(&error.emit_to_string_with_path(&self.text, &self.path))
+        })
+    }
+
+    pub fn validate(&self, module: &naga::Module) -> Result<naga::valid::ModuleInfo, String> {
+        let mut validator = naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::all(),
+        );
+        validator.validate(module).map_err(|error| {
+            self.remap_diagnostic(
+                &error.emit_to_string_with_path(&self.text, &self.path.to_string_lossy()),
+            )
+        })
+    }
+
+    /// naga's diagnostics refer to lines of the preprocessed text. This
+    /// rewrites their locations and line numbers to point at the files the
+    /// lines came from.
+    fn remap_diagnostic(&self, diagnostic: &str) -> String {
+        let header = format!("┌─ {}:", self.path.display());
+        let mut remapped = String::new();
+        for line in diagnostic.lines() {
+            if let Some((indent, location)) = line.split_once(&header) {
+                // "┌─ path:line:column"
+                if let Some((line_number, column)) = location.split_once(':') {
+                    if let Some(&(file, original)) = line_number
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|line_number| self.lines.get(line_number - 1))
+                    {
+                        remapped += &format!(
+                            "{}┌─ {}:{}:{}\n",
+                            indent,
+                            self.files[file].display(),
+                            original,
+                            column
+                        );
+                        continue;
+                    }
+                }
+            } else {
+                // "12 │ source line"
+                let trimmed = line.trim_start();
+                let digits = trimmed.len()
+                    - trimmed
+                        .trim_start_matches(|c: char| c.is_ascii_digit())
+                        .len();
+                if digits > 0 && trimmed[digits..].starts_with(" │") {
+                    let line_number: usize = trimmed[..digits].parse().unwrap();
+                    if let Some(&(_, original)) = self.lines.get(line_number - 1) {
+                        let width = line.len() - trimmed.len() + digits;
+                        remapped += &format!(
+                            "{:>width$}{}\n",
+                            original,
+                            &trimmed[digits..],
+                            width = width
+                        );
+                        continue;
+                    }
+                }
+            }
+            remapped += line;
+            remapped.push('\n');
+        }
+        remapped
+    }
+}
+
+/// One entry point of a shader, validated and checked against SDL's binding
+/// conventions, ready to be translated to any of the formats SDL takes.
+pub struct EntryPoint {
+    pub name: String,
+    pub stage: ShaderStage,
+    pub resources: ShaderResources,
+    module: naga::Module,
+    info: naga::valid::ModuleInfo,
+}
+
+impl EntryPoint {
+    pub fn new(
+        source: &ShaderSource,
+        module: naga::Module,
+        name: &str,
+        stage: ShaderStage,
+    ) -> Result<Self, String> {
+        let module = only_entry_point(module, name, stage);
+        let info = source.validate(&module)?;
+        let resources = reflect_resources(&module, &info, name, stage).map_err(|errors| {
+            format!(
+                "{} ({}) doesn't follow SDL's binding conventions:\n  {}",
+                source.path.display(),
+                name,
+                errors.join("\n  ")
+            )
+        })?;
+        Ok(Self {
+            name: name.to_owned(),
+            stage,
+            resources,
+            module,
+            info,
+        })
+    }
+
+    pub fn to_spv(&self) -> Result<Vec<u8>, String> {
+        let pipeline_options = naga::back::spv::PipelineOptions {
+            shader_stage: self.stage.into(),
+            entry_point: self.name.clone(),
+        };
+        let options = naga::back::spv::Options {
+            ..Default::default()
+        };
+        let spv =
+            naga::back::spv::write_vec(&self.module, &self.info, &options, Some(&pipeline_options))
+                .map_err(|error| format!("Couldn't write SPIR-V for {}: {}", self.name, error))?;
+        let bytes = spv
+            .iter()
+            .fold(Vec::with_capacity(spv.len() * 4), |mut v, w| {
+                v.extend_from_slice(&w.to_le_bytes());
+                v
+            });
+        Ok(bytes)
+    }
+
+    pub fn to_msl(&self) -> Result<String, String> {
+        let options = naga::back::msl::Options {
+            lang_version: (2, 0),
+            per_entry_point_map: [(self.name.clone(), self.resources.msl_resources())].into(),
+            fake_missing_bindings: false,
+            ..Default::default()
+        };
+        let pipeline_options = naga::back::msl::PipelineOptions::default();
+        let (msl, translation_info) =
+            naga::back::msl::write_string(&self.module, &self.info, &options, &pipeline_options)
+                .map_err(|error| format!("Couldn't write MSL for {}: {}", self.name, error))?;
+        // SDL looks the entry point up by name, so it can't be renamed
+        self.check_name(&translation_info.entry_point_names[0])?;
+        Ok(msl)
+    }
+
+    pub fn to_hlsl(&self) -> Result<String, String> {
+        let options = naga::back::hlsl::Options {
+            // DXIL needs at least shader model 6
+            shader_model: naga::back::hlsl::ShaderModel::V6_0,
+            binding_map: self.resources.hlsl_binding_map(),
+            fake_missing_bindings: false,
+            ..Default::default()
+        };
+        let mut hlsl = String::new();
+        let reflection_info = naga::back::hlsl::Writer::new(&mut hlsl, &options)
+            .write(&self.module, &self.info, None)
+            .map_err(|error| format!("Couldn't write HLSL for {}: {}", self.name, error))?;
+        self.check_name(&reflection_info.entry_point_names[0])?;
+        Ok(texcoord_semantics(&hlsl))
+    }
+
+    fn check_name<E: std::fmt::Display>(
+        &self,
+        translated: &Result<String, E>,
+    ) -> Result<(), String> {
+        match translated {
+            Ok(name) if *name == self.name => Ok(()),
+            Ok(name) => Err(format!("{} was renamed to {}", self.name, name)),
+            Err(error) => Err(format!("Couldn't translate {}: {}", self.name, error)),
+        }
+    }
+}
+
+/// SDL's D3D12 backend feeds vertex attributes in as TEXCOORD<location>,
+/// while naga names user locations LOC<location> and has no option to change
+/// that. Semantics only have to match between stages, so every one naga wrote
+/// is renamed, but nothing else that happens to contain "LOC" is touched.
+fn texcoord_semantics(hlsl: &str) -> String {
+    const LOCATION: &str = " : LOC";
+    let mut renamed = String::with_capacity(hlsl.len());
+    let mut rest = hlsl;
+    while let Some(start) = rest.find(LOCATION) {
+        let after = &rest[start + LOCATION.len()..];
+        let digits = after.len() - after.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        // naga ends a semantic with the `;` of a struct member, or the `,` or
+        // `)` of an entry point argument
+        let is_semantic = digits > 0 && after[digits..].starts_with([';', ',', ')']);
+        renamed.push_str(&rest[..start]);
+        renamed.push_str(if is_semantic { " : TEXCOORD" } else { LOCATION });
+        rest = after;
+    }
+    renamed.push_str(rest);
+    renamed
+}
+
+/// Drops every entry point but the one we're compiling. The MSL and HLSL
+/// writers translate the whole module, and we want one entry point per file
+/// with only its own bindings.
+fn only_entry_point(
+    mut module: naga::Module,
+    entrypoint: &str,
+    stage: ShaderStage,
+) -> naga::Module {
+    module
+        .entry_points
+        .retain(|ep| ep.name == entrypoint && ep.stage == stage.into());
+    module
+}
+
+/// The number of each kind of resource an entry point uses, which SDL needs
+/// to know when creating the shader.
+#[derive(Default)]
+pub struct ShaderResources {
+    /// Every binding the entry point uses, sorted by group and binding.
+    bindings: Vec<(naga::ResourceBinding, ResourceKind)>,
+    pub num_samplers: u32,
+    pub num_storage_textures: u32,
+    pub num_storage_buffers: u32,
+    pub num_uniform_buffers: u32,
+}
+
+impl ShaderResources {
+    /// Maps our bindings onto SDL's HLSL registers. Each bind group becomes
+    /// a register space, with textures and storage buffers sharing the t
+    /// registers.
+    fn hlsl_binding_map(&self) -> naga::back::hlsl::BindingMap {
+        let mut next_register = [0; 3];
+        self.bindings
+            .iter()
+            .map(|(binding, kind)| {
+                let class = match kind {
+                    ResourceKind::SampledTexture
+                    | ResourceKind::StorageTexture
+                    | ResourceKind::StorageBuffer => 0,
+                    ResourceKind::Sampler => 1,
+                    ResourceKind::UniformBuffer => 2,
+                };
+                let register = next_register[class];
+                next_register[class] += 1;
+                let target = naga::back::hlsl::BindTarget {
+                    space: binding.group as u8,
+                    register,
+                    binding_array_size: None,
+                };
+                (binding.clone(), target)
+            })
+            .collect()
+    }
+
+    /// Maps our bindings onto SDL's Metal argument table, where the uniform
+    /// buffers come before the storage buffers.
+    fn msl_resources(&self) -> naga::back::msl::EntryPointResources {
+        let mut next_texture = 0;
+        let mut next_sampler = 0;
+        let mut next_uniform_buffer = 0;
+        let mut next_storage_buffer = self.num_uniform_buffers as u8;
+        let resources = self
+            .bindings
+            .iter()
+            .map(|(binding, kind)| {
+                let mut target = naga::back::msl::BindTarget::default();
+                let next_slot = match kind {
+                    ResourceKind::SampledTexture | ResourceKind::StorageTexture => {
+                        &mut next_texture
+                    }
+                    ResourceKind::Sampler => &mut next_sampler,
+                    ResourceKind::StorageBuffer => &mut next_storage_buffer,
+                    ResourceKind::UniformBuffer => &mut next_uniform_buffer,
+                };
+                let slot = *next_slot;
+                *next_slot += 1;
+                match kind {
+                    ResourceKind::SampledTexture | ResourceKind::StorageTexture => {
+                        target.texture = Some(slot)
+                    }
+                    ResourceKind::Sampler => {
+                        target.sampler = Some(naga::back::msl::BindSamplerTarget::Resource(slot))
+                    }
+                    ResourceKind::StorageBuffer | ResourceKind::UniformBuffer => {
+                        target.buffer = Some(slot)
+                    }
+                }
+                (binding.clone(), target)
+            })
+            .collect();
+        naga::back::msl::EntryPointResources {
+            resources,
+            ..Default::default()
+        }
+    }
+}
+
+/// Resource kinds, in the order SDL expects them within a bind group.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum ResourceKind {
+    SampledTexture,
+    Sampler,
+    StorageTexture,
+    StorageBuffer,
+    UniformBuffer,
+}
+
+impl ResourceKind {
+    fn of(module: &naga::Module, var: &naga::GlobalVariable) -> Result<Option<Self>, String> {
+        let kind = match var.space {
+            naga::AddressSpace::Uniform => Self::UniformBuffer,
+            naga::AddressSpace::Storage { .. } => Self::StorageBuffer,
+            naga::AddressSpace::Handle => match module.types[var.ty].inner {
+                naga::TypeInner::Image {
+                    class: naga::ImageClass::Storage { .. },
+                    ..
+                } => Self::StorageTexture,
+                naga::TypeInner::Image { .. } => Self::SampledTexture,
+                naga::TypeInner::Sampler { .. } => Self::Sampler,
+                _ => return Err(format!("`{}` has an unsupported type", name_of(var))),
+            },
+            naga::AddressSpace::PushConstant => {
+                return Err(format!(
+                    "`{}` is a push constant, SDL only supports uniform buffers",
+                    name_of(var)
+                ))
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(kind))
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::SampledTexture => "sampled texture",
+            Self::Sampler => "sampler",
+            Self::StorageTexture => "storage texture",
+            Self::StorageBuffer => "storage buffer",
+            Self::UniformBuffer => "uniform buffer",
+        }
+    }
+}
+
+fn name_of(var: &naga::GlobalVariable) -> &str {
+    var.name.as_deref().unwrap_or("<unnamed>")
+}
+
+/// Counts the resources used by an entry point, checking them against the
+/// bind group layout SDL expects for the stage:
+///   * textures, samplers and storage buffers in the stage's resource group,
+///     with sampled textures first, then storage textures, then storage
+///     buffers
+///   * uniform buffers in the group after it
+///   * bindings within a group counting up from 0 without gaps
+fn reflect_resources(
+    module: &naga::Module,
+    info: &naga::valid::ModuleInfo,
+    entrypoint: &str,
+    stage: ShaderStage,
+) -> Result<ShaderResources, Vec<String>> {
+    let stage_name = match stage {
+        ShaderStage::Vertex => "vertex",
+        ShaderStage::Fragment => "fragment",
+    };
+    let index = module
+        .entry_points
+        .iter()
+        .position(|ep| ep.name == entrypoint && ep.stage == stage.into())
+        .ok_or_else(|| {
+            vec![format!(
+                "no {} entry point named `{}`",
+                stage_name, entrypoint
+            )]
+        })?;
+    let function_info = info.get_entry_point(index);
+    let (resource_group, uniform_group) = stage.bind_groups();
+
+    let mut errors = Vec::new();
+    let mut bindings = Vec::new();
+    for (handle, var) in module.global_variables.iter() {
+        if function_info[handle].is_empty() {
+            continue;
+        }
+        let kind = match ResourceKind::of(module, var) {
+            Ok(Some(kind)) => kind,
+            Ok(None) => continue,
+            Err(error) => {
+                errors.push(error);
+                continue;
+            }
+        };
+        let binding = var
+            .binding
+            .as_ref()
+            .expect("resources always have a binding");
+        let expected_group = if kind == ResourceKind::UniformBuffer {
+            uniform_group
+        } else {
+            resource_group
+        };
+        if binding.group != expected_group {
+            errors.push(format!(
+                "`{}` is a {} in @group({}), but {} shaders must put them in @group({})",
+                name_of(var),
+                kind.name(),
+                binding.group,
+                stage_name,
+                expected_group
+            ));
+            continue;
+        }
+        bindings.push((binding.group, binding.binding, kind, name_of(var)));
+    }
+    bindings.sort();
+
+    let mut resources = ShaderResources::default();
+    let mut num_textures_to_sample = 0;
+    let mut previous: Option<(u32, u32, ResourceKind, &str)> = None;
+    for &(group, binding, kind, name) in &bindings {
+        let expected_binding = match previous {
+            Some((previous_group, previous_binding, _, _)) if previous_group == group => {
+                previous_binding + 1
+            }
+            _ => 0,
+        };
+        if binding != expected_binding {
+            errors.push(format!(
+                "`{}` is at @binding({}) in @group({}), expected @binding({})",
+                name, binding, group, expected_binding
+            ));
+        }
+        if let Some((previous_group, _, previous_kind, previous_name)) = previous {
+            if previous_group == group && ordering_rank(previous_kind) > ordering_rank(kind) {
+                errors.push(format!(
+                    "{} `{}` must be bound before {} `{}`",
+                    kind.name(),
+                    name,
+                    previous_kind.name(),
+                    previous_name
+                ));
+            }
+        }
+        match kind {
+            ResourceKind::SampledTexture => num_textures_to_sample += 1,
+            ResourceKind::Sampler => resources.num_samplers += 1,
+            ResourceKind::StorageTexture => resources.num_storage_textures += 1,
+            ResourceKind::StorageBuffer => resources.num_storage_buffers += 1,
+            ResourceKind::UniformBuffer => resources.num_uniform_buffers += 1,
+        }
+        resources
+            .bindings
+            .push((naga::ResourceBinding { group, binding }, kind));
+        previous = Some((group, binding, kind, name));
+    }
+    // SDL binds textures and samplers together, so they have to pair up
+    if num_textures_to_sample != resources.num_samplers {
+        errors.push(format!(
+            "{} sampled textures but {} samplers, SDL binds them in pairs",
+            num_textures_to_sample, resources.num_samplers
+        ));
+    }
+
+    if errors.is_empty() {
+        Ok(resources)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Sampled textures and their samplers can be interleaved, everything else
+/// has to be in `ResourceKind` order.
+fn ordering_rank(kind: ResourceKind) -> ResourceKind {
+    match kind {
+        ResourceKind::Sampler => ResourceKind::SampledTexture,
+        kind => kind,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first_location(diagnostic: &str) -> &str {
+        let start = diagnostic.find("┌─ ").expect("diagnostic has a location") + "┌─ ".len();
+        diagnostic[start..].lines().next().unwrap()
+    }
+
+    /// The shader the app draws the cube with.
+    fn cube_shader() -> (ShaderSource, naga::Module) {
+        let source = ShaderSource::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../data/shaders/shader.wgsl"
+        ))
+        .unwrap();
+        let module = source.parse().unwrap();
+        (source, module)
+    }
+
+    /// Reflects an entry point's resources, keeping the errors apart.
+    fn reflect(
+        source: &ShaderSource,
+        module: &naga::Module,
+        name: &str,
+        stage: ShaderStage,
+    ) -> Result<ShaderResources, Vec<String>> {
+        let info = source.validate(module).unwrap();
+        reflect_resources(module, &info, name, stage)
+    }
+
+    fn binding_errors(path: &str, name: &str, stage: ShaderStage) -> Vec<String> {
+        let source = ShaderSource::read(path).unwrap();
+        let module = source.parse().unwrap();
+        match reflect(&source, &module, name, stage) {
+            Ok(_) => panic!("{} follows SDL's binding conventions", path),
+            Err(errors) => errors,
+        }
+    }
+
+    /// The execution model and name of each OpEntryPoint in a SPIR-V binary.
+    fn spv_entry_points(spv: &[u8]) -> Vec<(u32, String)> {
+        const OP_ENTRY_POINT: u32 = 15;
+        let words: Vec<u32> = spv
+            .chunks_exact(4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+            .collect();
+        // Instructions start after the five word header
+        let mut entry_points = Vec::new();
+        let mut offset = 5;
+        while offset < words.len() {
+            let word_count = (words[offset] >> 16) as usize;
+            let opcode = words[offset] & 0xffff;
+            if opcode == OP_ENTRY_POINT {
+                let execution_model = words[offset + 1];
+                let name: Vec<u8> = words[offset + 3..offset + word_count]
+                    .iter()
+                    .flat_map(|word| word.to_le_bytes())
+                    .take_while(|byte| *byte != 0)
+                    .collect();
+                entry_points.push((execution_model, String::from_utf8(name).unwrap()));
+            }
+            offset += word_count;
+        }
+        entry_points
+    }
+
+    #[test]
+    fn parse_errors_point_at_the_typo() {
+        let source = ShaderSource::read("tests/fixtures/parse_error.wgsl").unwrap();
+        let diagnostic = source.parse().unwrap_err();
+        assert!(
+            diagnostic.contains("unknown type: 'VertexOuput'"),
+            "{}",
+            diagnostic
+        );
+        assert_eq!(
+            first_location(&diagnostic),
+            "tests/fixtures/parse_error.wgsl:7:14"
+        );
+    }
+
+    #[test]
+    fn validation_errors_point_at_the_bad_expression() {
+        let source = ShaderSource::read("tests/fixtures/validation_error.wgsl").unwrap();
+        let module = source.parse().unwrap();
+        let diagnostic = source.validate(&module).unwrap_err();
+        assert!(
+            first_location(&diagnostic).starts_with("tests/fixtures/validation_error.wgsl:8:"),
+            "{}",
+            diagnostic
+        );
+    }
+
+    #[test]
+    fn includes_are_pasted_once_and_defines_toggle_code() {
+        let source = ShaderSource::read("tests/fixtures/preprocess/main.wgsl").unwrap();
+        assert_eq!(
+            source.files,
+            [
+                PathBuf::from("tests/fixtures/preprocess/main.wgsl"),
+                PathBuf::from("tests/fixtures/preprocess/include/common.wgsl"),
+            ]
+        );
+        assert_eq!(source.text.matches("struct VertexOutput").count(), 1);
+        assert!(source.text.contains("return fancy(in.color);"));
+        assert!(!source.text.contains("return in.color;"));
+        source.validate(&source.parse().unwrap()).unwrap();
+
+        let fancy_line = source
+            .text
+            .lines()
+            .position(|line| line.contains("return color.bgra;"))
+            .unwrap();
+        assert_eq!(source.lines[fancy_line], (1, 8));
+    }
+
+    #[test]
+    fn includes_are_matched_by_the_file_they_point_at() {
+        let source = ShaderSource::read("tests/fixtures/preprocess/relative.wgsl").unwrap();
+        assert_eq!(
+            source.files,
+            [
+                PathBuf::from("tests/fixtures/preprocess/relative.wgsl"),
+                PathBuf::from("tests/fixtures/preprocess/./include/common.wgsl"),
+            ]
+        );
+        assert_eq!(source.text.matches("struct VertexOutput").count(), 1);
+        source.validate(&source.parse().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn errors_in_included_files_point_at_them() {
+        let source = ShaderSource::read("tests/fixtures/preprocess/broken_include.wgsl").unwrap();
+        let diagnostic = source.parse().unwrap_err();
+        assert_eq!(
+            first_location(&diagnostic),
+            "tests/fixtures/preprocess/include/broken.wgsl:7:18"
+        );
+        assert!(
+            diagnostic.contains("7 │     return 1.0 + ;"),
+            "{}",
+            diagnostic
+        );
+    }
+
+    #[test]
+    fn unterminated_conditions_are_reported() {
+        let error = ShaderSource::read("tests/fixtures/preprocess/unterminated.wgsl")
+            .err()
+            .unwrap();
+        assert_eq!(
+            error,
+            "tests/fixtures/preprocess/unterminated.wgsl: #ifdef without an #endif"
+        );
+    }
+
+    #[test]
+    fn cube_shader_has_a_vertex_and_a_fragment_entry_point() {
+        let (_, module) = cube_shader();
+        let entry_points: Vec<_> = module
+            .entry_points
+            .iter()
+            .map(|entry_point| (entry_point.name.as_str(), entry_point.stage))
+            .collect();
+        assert_eq!(
+            entry_points,
+            [
+                ("vs_main", naga::ShaderStage::Vertex),
+                ("fs_main", naga::ShaderStage::Fragment),
+            ]
+        );
+    }
+
+    #[test]
+    fn spv_starts_with_the_magic_number() {
+        let (source, module) = cube_shader();
+        let entry_point = EntryPoint::new(&source, module, "vs_main", ShaderStage::Vertex).unwrap();
+        let spv = entry_point.to_spv().unwrap();
+        assert_eq!(spv.len() % 4, 0);
+        assert_eq!(u32::from_le_bytes(spv[..4].try_into().unwrap()), 0x07230203);
+    }
+
+    #[test]
+    fn spv_contains_only_the_requested_entry_point() {
+        // SPIR-V's execution models for the two stages
+        const VERTEX: u32 = 0;
+        const FRAGMENT: u32 = 4;
+        for (name, stage, execution_model) in [
+            ("vs_main", ShaderStage::Vertex, VERTEX),
+            ("fs_main", ShaderStage::Fragment, FRAGMENT),
+        ] {
+            let (source, module) = cube_shader();
+            let entry_point = EntryPoint::new(&source, module, name, stage).unwrap();
+            assert_eq!(entry_point.stage, stage);
+            assert_eq!(
+                spv_entry_points(&entry_point.to_spv().unwrap()),
+                [(execution_model, name.to_owned())]
+            );
+        }
+    }
+
+    #[test]
+    fn cube_shader_resources_are_counted() {
+        let (source, module) = cube_shader();
+        let vertex = reflect(&source, &module, "vs_main", ShaderStage::Vertex).unwrap();
+        assert_eq!(vertex.num_uniform_buffers, 2);
+        assert_eq!(vertex.num_samplers, 0);
+        assert_eq!(vertex.num_storage_textures, 0);
+        assert_eq!(vertex.num_storage_buffers, 0);
+
+        let fragment = reflect(&source, &module, "fs_main", ShaderStage::Fragment).unwrap();
+        assert_eq!(fragment.num_uniform_buffers, 0);
+        assert_eq!(fragment.num_samplers, 0);
+        assert_eq!(fragment.num_storage_textures, 0);
+        assert_eq!(fragment.num_storage_buffers, 0);
+    }
+
+    #[test]
+    fn resources_in_the_wrong_group_are_rejected() {
+        assert_eq!(
+            binding_errors(
+                "tests/fixtures/bindings/wrong_group.wgsl",
+                "vs_main",
+                ShaderStage::Vertex
+            ),
+            ["`transform` is a uniform buffer in @group(0), but vertex shaders must put them in @group(1)"]
+        );
+    }
+
+    #[test]
+    fn gaps_between_bindings_are_rejected() {
+        assert_eq!(
+            binding_errors(
+                "tests/fixtures/bindings/binding_gap.wgsl",
+                "vs_main",
+                ShaderStage::Vertex
+            ),
+            ["`model` is at @binding(2) in @group(1), expected @binding(1)"]
+        );
+    }
+
+    #[test]
+    fn resources_out_of_order_are_rejected() {
+        assert_eq!(
+            binding_errors(
+                "tests/fixtures/bindings/ordering.wgsl",
+                "fs_main",
+                ShaderStage::Fragment
+            ),
+            ["sampled texture `color_texture` must be bound before storage buffer `tint`"]
+        );
+    }
+
+    #[test]
+    fn textures_without_their_own_sampler_are_rejected() {
+        assert_eq!(
+            binding_errors(
+                "tests/fixtures/bindings/unpaired_texture.wgsl",
+                "fs_main",
+                ShaderStage::Fragment
+            ),
+            ["2 sampled textures but 1 samplers, SDL binds them in pairs"]
+        );
+    }
+
+    fn fragment_fixture() -> EntryPoint {
+        let source = ShaderSource::read("tests/fixtures/bindings/fragment.wgsl").unwrap();
+        let module = source.parse().unwrap();
+        EntryPoint::new(&source, module, "fs_main", ShaderStage::Fragment).unwrap()
+    }
+
+    #[test]
+    fn hlsl_uses_sdls_registers_and_semantics() {
+        let (source, module) = cube_shader();
+        let vertex = EntryPoint::new(&source, module, "vs_main", ShaderStage::Vertex)
+            .unwrap()
+            .to_hlsl()
+            .unwrap();
+        assert!(
+            vertex.contains("vs_main(float4 position : TEXCOORD0, float4 color : TEXCOORD1)"),
+            "{}",
+            vertex
+        );
+        assert!(vertex.contains("float4 color_1 : TEXCOORD0;"), "{}", vertex);
+        assert!(
+            vertex.contains("r_globals : register(b0, space1)"),
+            "{}",
+            vertex
+        );
+        assert!(
+            vertex.contains("r_locals : register(b1, space1)"),
+            "{}",
+            vertex
+        );
+        assert!(!vertex.contains(": LOC"), "{}", vertex);
+
+        let fragment = fragment_fixture().to_hlsl().unwrap();
+        assert!(fragment.contains(" fs_main("), "{}", fragment);
+        assert!(
+            fragment.contains("float2 uv_1 : TEXCOORD0;"),
+            "{}",
+            fragment
+        );
+        assert!(
+            fragment.contains("uint index_1 : TEXCOORD1;"),
+            "{}",
+            fragment
+        );
+        assert!(fragment.contains(": SV_Target0"), "{}", fragment);
+        for register in [
+            "color_texture : register(t0, space2)",
+            "color_sampler : register(s0, space2)",
+            "palette : register(t1, space2)",
+            "material : register(b0, space3)",
+        ] {
+            assert!(fragment.contains(register), "{}\n{}", register, fragment);
+        }
+    }
+
+    #[test]
+    fn msl_uses_sdls_argument_table() {
+        let (source, module) = cube_shader();
+        let vertex = EntryPoint::new(&source, module, "vs_main", ShaderStage::Vertex)
+            .unwrap()
+            .to_msl()
+            .unwrap();
+        assert!(
+            vertex.contains("vertex vs_mainOutput vs_main("),
+            "{}",
+            vertex
+        );
+        assert!(vertex.contains("r_globals [[buffer(0)]]"), "{}", vertex);
+        assert!(vertex.contains("r_locals [[buffer(1)]]"), "{}", vertex);
+        assert!(vertex.contains("position [[attribute(0)]]"), "{}", vertex);
+        assert!(vertex.contains("color [[attribute(1)]]"), "{}", vertex);
+
+        // Uniform buffers come before storage buffers
+        let fragment = fragment_fixture().to_msl().unwrap();
+        assert!(
+            fragment.contains("fragment fs_mainOutput fs_main("),
+            "{}",
+            fragment
+        );
+        for binding in [
+            "color_texture [[texture(0)]]",
+            "color_sampler [[sampler(0)]]",
+            "material [[buffer(0)]]",
+            "palette [[buffer(1)]]",
+        ] {
+            assert!(fragment.contains(binding), "{}\n{}", binding, fragment);
+        }
+    }
+
+    #[test]
+    fn only_location_semantics_become_texcoords() {
+        assert_eq!(
+            texcoord_semantics("float4 a : LOC0;\nf(float4 b : LOC12, uint c : LOC3)"),
+            "float4 a : TEXCOORD0;\nf(float4 b : TEXCOORD12, uint c : TEXCOORD3)"
+        );
+        let untouched = "x ? y : LOCAL; x ? y : LOC_1; x ? y : LOC2 + 1;";
+        assert_eq!(texcoord_semantics(untouched), untouched);
+    }
+}
@@ -0,0 +1,14 @@
+// Skips @binding(1)
+
+@group(1)
+@binding(0)
+var<uniform> view: mat4x4<f32>;
+
+@group(1)
+@binding(2)
+var<uniform> model: mat4x4<f32>;
+
+@vertex
+fn vs_main(@location(0) position: vec4<f32>) -> @builtin(position) vec4<f32> {
+    return view * model * position;
+}
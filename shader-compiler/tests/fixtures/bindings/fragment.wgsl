@@ -0,0 +1,26 @@
+// One of everything a fragment shader can bind, in SDL's order
+
+struct Material {
+    tint: vec4<f32>,
+};
+
+@group(2)
+@binding(0)
+var color_texture: texture_2d<f32>;
+
+@group(2)
+@binding(1)
+var color_sampler: sampler;
+
+@group(2)
+@binding(2)
+var<storage, read> palette: array<vec4<f32>, 16>;
+
+@group(3)
+@binding(0)
+var<uniform> material: Material;
+
+@fragment
+fn fs_main(@location(0) uv: vec2<f32>, @location(1) @interpolate(flat) index: u32) -> @location(0) vec4<f32> {
+    return material.tint * palette[index] * textureSample(color_texture, color_sampler, uv);
+}
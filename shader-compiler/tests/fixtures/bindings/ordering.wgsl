@@ -0,0 +1,18 @@
+// Storage buffers have to come after the textures and their samplers
+
+@group(2)
+@binding(0)
+var<storage, read> tint: vec4<f32>;
+
+@group(2)
+@binding(1)
+var color_texture: texture_2d<f32>;
+
+@group(2)
+@binding(2)
+var color_sampler: sampler;
+
+@fragment
+fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    return tint * textureSample(color_texture, color_sampler, uv);
+}
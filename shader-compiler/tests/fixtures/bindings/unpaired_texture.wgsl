@@ -0,0 +1,19 @@
+// Two textures sampled with the same sampler
+
+@group(2)
+@binding(0)
+var color_texture: texture_2d<f32>;
+
+@group(2)
+@binding(1)
+var detail_texture: texture_2d<f32>;
+
+@group(2)
+@binding(2)
+var color_sampler: sampler;
+
+@fragment
+fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    return textureSample(color_texture, color_sampler, uv)
+        * textureSample(detail_texture, color_sampler, uv);
+}
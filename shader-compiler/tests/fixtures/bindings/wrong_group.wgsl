@@ -0,0 +1,10 @@
+// Vertex shaders take their uniform buffers from @group(1)
+
+@group(0)
+@binding(0)
+var<uniform> transform: mat4x4<f32>;
+
+@vertex
+fn vs_main(@location(0) position: vec4<f32>) -> @builtin(position) vec4<f32> {
+    return transform * position;
+}
@@ -0,0 +1,10 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+};
+
+@vertex
+fn vs_main(@location(0) position: vec4<f32>) -> VertexOutput {
+    var out: VertexOuput;
+    out.position = position;
+    return out;
+}
@@ -0,0 +1,6 @@
+#include "include/broken.wgsl"
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
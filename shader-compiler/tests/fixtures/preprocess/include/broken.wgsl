@@ -0,0 +1,8 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+fn unused() -> f32 {
+    return 1.0 + ;
+}
@@ -0,0 +1,10 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+#ifdef FANCY
+fn fancy(color: vec4<f32>) -> vec4<f32> {
+    return color.bgra;
+}
+#endif
@@ -0,0 +1,12 @@
+#define FANCY
+#include "include/common.wgsl"
+#include "include/common.wgsl"
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+#ifdef FANCY
+    return fancy(in.color);
+#else
+    return in.color;
+#endif
+}
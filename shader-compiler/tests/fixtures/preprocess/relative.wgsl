@@ -0,0 +1,8 @@
+#include "./include/common.wgsl"
+#include "include/common.wgsl"
+#include "include/../include/common.wgsl"
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
@@ -0,0 +1,2 @@
+#ifdef FANCY
+fn nothing() {}
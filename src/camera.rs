@@ -0,0 +1,303 @@
+use std::{f32::consts::FRAC_PI_2, time::Duration};
+
+use glam::{Mat4, Vec3};
+
+/// The demo has always viewed the scene with -Y as up.
+const UP: Vec3 = Vec3::new(0.0, -1.0, 0.0);
+/// Keeps the camera from reaching the poles, where the view matrix would
+/// flip around.
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+const MIN_DISTANCE: f32 = 15.0;
+const MAX_DISTANCE: f32 = 500.0;
+/// Radians per pixel dragged.
+const ROTATE_SPEED: f32 = 0.005;
+/// How much one wheel notch scales the distance by.
+const ZOOM_FACTOR: f32 = 0.9;
+/// How quickly the camera catches up with the input. Higher is snappier.
+const SMOOTHING: f32 = 15.0;
+/// Radians per pixel of mouse movement while flying.
+const LOOK_SPEED: f32 = 0.003;
+/// Units per second while flying.
+const FLY_SPEED: f32 = 50.0;
+const SPRINT_MULTIPLIER: f32 = 3.0;
+/// How far ahead of the fly camera the orbit camera picks its target when
+/// switching back.
+const ORBIT_DISTANCE: f32 = 50.0;
+
+/// Where the camera is relative to its target.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Orbit {
+    yaw: f32,
+    pitch: f32,
+    distance: f32,
+}
+
+impl Orbit {
+    fn offset(&self) -> Vec3 {
+        direction(self.yaw, self.pitch) * self.distance
+    }
+}
+
+/// The unit vector for a yaw around Y, starting from +Z, and a pitch towards
+/// +Y.
+fn direction(yaw: f32, pitch: f32) -> Vec3 {
+    let (sin_yaw, cos_yaw) = yaw.sin_cos();
+    let (sin_pitch, cos_pitch) = pitch.sin_cos();
+    Vec3::new(cos_pitch * sin_yaw, sin_pitch, cos_pitch * cos_yaw)
+}
+
+/// A camera that circles a target point. Dragging with the left mouse button
+/// rotates around it and the wheel zooms in and out, with the camera easing
+/// towards where the input puts it rather than jumping there.
+pub struct OrbitCamera {
+    pub target: Vec3,
+    /// Where the input has asked the camera to be.
+    goal: Orbit,
+    /// Where the camera is, somewhere between where it was and `goal`.
+    current: Orbit,
+}
+
+impl OrbitCamera {
+    pub fn new(position: Vec3, target: Vec3) -> Self {
+        let offset = position - target;
+        let distance = offset.length().clamp(MIN_DISTANCE, MAX_DISTANCE);
+        let orbit = Orbit {
+            yaw: offset.x.atan2(offset.z),
+            pitch: (offset.y / offset.length())
+                .asin()
+                .clamp(-MAX_PITCH, MAX_PITCH),
+            distance,
+        };
+        Self {
+            target,
+            goal: orbit,
+            current: orbit,
+        }
+    }
+
+    /// Rotates by a mouse movement, in pixels.
+    pub fn rotate(&mut self, dx: f32, dy: f32) {
+        self.goal.yaw -= dx * ROTATE_SPEED;
+        self.goal.pitch = (self.goal.pitch + dy * ROTATE_SPEED).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    /// Zooms in by a number of wheel notches, or out if negative.
+    pub fn zoom(&mut self, notches: f32) {
+        self.goal.distance =
+            (self.goal.distance * ZOOM_FACTOR.powf(notches)).clamp(MIN_DISTANCE, MAX_DISTANCE);
+    }
+
+    /// Moves the camera towards where the input has put it.
+    pub fn update(&mut self, elapsed: Duration) {
+        let t = 1.0 - (-SMOOTHING * elapsed.as_secs_f32()).exp();
+        let lerp = |from: f32, to: f32| from + (to - from) * t;
+        self.current = Orbit {
+            yaw: lerp(self.current.yaw, self.goal.yaw),
+            pitch: lerp(self.current.pitch, self.goal.pitch),
+            distance: lerp(self.current.distance, self.goal.distance),
+        };
+    }
+
+    pub fn position(&self) -> Vec3 {
+        self.target + self.current.offset()
+    }
+
+    pub fn view(&self) -> Mat4 {
+        Mat4::look_at_rh(self.position(), self.target, UP)
+    }
+}
+
+/// A first person camera that looks around with the mouse and flies in the
+/// direction it's facing.
+pub struct FlyCamera {
+    pub position: Vec3,
+    yaw: f32,
+    pitch: f32,
+}
+
+impl FlyCamera {
+    pub fn new(position: Vec3, target: Vec3) -> Self {
+        let facing = (target - position).normalize();
+        Self {
+            position,
+            yaw: facing.x.atan2(facing.z),
+            pitch: facing.y.asin().clamp(-MAX_PITCH, MAX_PITCH),
+        }
+    }
+
+    /// Turns by a relative mouse movement, in pixels.
+    pub fn look(&mut self, dx: f32, dy: f32) {
+        self.yaw -= dx * LOOK_SPEED;
+        self.pitch = (self.pitch + dy * LOOK_SPEED).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    pub fn forward(&self) -> Vec3 {
+        direction(self.yaw, self.pitch)
+    }
+
+    /// Flies for `elapsed` in the direction of `movement`, which is relative
+    /// to the camera: +X is right, +Y is up and +Z is forward. A length of 1
+    /// is full speed, and anything longer, such as moving diagonally with
+    /// the keyboard, is capped to it.
+    pub fn update(&mut self, movement: Vec3, sprint: bool, elapsed: Duration) {
+        let forward = self.forward();
+        // Right as it appears on screen, after the projection's X flip
+        let right = UP.cross(forward).normalize();
+        let velocity = (right * movement.x + UP * movement.y + forward * movement.z)
+            .clamp_length_max(1.0)
+            * FLY_SPEED
+            * if sprint { SPRINT_MULTIPLIER } else { 1.0 };
+        self.position += velocity * elapsed.as_secs_f32();
+    }
+
+    pub fn view(&self) -> Mat4 {
+        Mat4::look_to_rh(self.position, self.forward(), UP)
+    }
+}
+
+/// Whichever camera is in control.
+pub enum Camera {
+    Orbit(OrbitCamera),
+    Fly(FlyCamera),
+}
+
+impl Camera {
+    /// Switches between orbiting and flying, starting the new camera where
+    /// the old one was and facing the same way.
+    pub fn toggle(&mut self) {
+        *self = match self {
+            Camera::Orbit(orbit) => Camera::Fly(FlyCamera::new(orbit.position(), orbit.target)),
+            Camera::Fly(fly) => Camera::Orbit(OrbitCamera::new(
+                fly.position,
+                fly.position + fly.forward() * ORBIT_DISTANCE,
+            )),
+        }
+    }
+
+    pub fn view(&self) -> Mat4 {
+        match self {
+            Camera::Orbit(orbit) => orbit.view(),
+            Camera::Fly(fly) => fly.view(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SETTLE: Duration = Duration::from_secs(10);
+
+    fn assert_near(a: Vec3, b: Vec3) {
+        assert!(a.abs_diff_eq(b, 1e-3), "{} != {}", a, b);
+    }
+
+    #[test]
+    fn view_matches_looking_from_the_starting_position() {
+        let position = Vec3::new(0.0, 50.0, -50.0);
+        let camera = OrbitCamera::new(position, Vec3::ZERO);
+        assert_near(camera.position(), position);
+        assert!(camera
+            .view()
+            .abs_diff_eq(Mat4::look_at_rh(position, Vec3::ZERO, UP), 1e-4));
+        // The target is straight ahead, down the view space's -Z
+        assert_near(
+            camera.view().transform_point3(Vec3::ZERO),
+            Vec3::new(0.0, 0.0, -position.length()),
+        );
+    }
+
+    #[test]
+    fn dragging_orbits_at_the_same_distance() {
+        let target = Vec3::new(5.0, 0.0, 0.0);
+        let mut camera = OrbitCamera::new(Vec3::new(5.0, 0.0, 50.0), target);
+        camera.rotate(-FRAC_PI_2 / ROTATE_SPEED, 0.0);
+        camera.update(SETTLE);
+        assert_near(camera.position(), Vec3::new(55.0, 0.0, 0.0));
+        assert_near(
+            camera.view().transform_point3(target),
+            Vec3::new(0.0, 0.0, -50.0),
+        );
+    }
+
+    #[test]
+    fn pitch_stops_short_of_the_poles() {
+        let mut camera = OrbitCamera::new(Vec3::new(0.0, 0.0, 50.0), Vec3::ZERO);
+        camera.rotate(0.0, 100_000.0);
+        camera.update(SETTLE);
+        assert_eq!(camera.current.pitch, MAX_PITCH);
+        assert!(camera.position().z > 0.0);
+        assert!(!camera.view().is_nan());
+
+        camera.rotate(0.0, -200_000.0);
+        camera.update(SETTLE);
+        assert_eq!(camera.current.pitch, -MAX_PITCH);
+    }
+
+    #[test]
+    fn zoom_is_clamped() {
+        let mut camera = OrbitCamera::new(Vec3::new(0.0, 0.0, 50.0), Vec3::ZERO);
+        camera.zoom(1000.0);
+        camera.update(SETTLE);
+        assert_near(camera.position(), Vec3::new(0.0, 0.0, MIN_DISTANCE));
+        camera.zoom(-1000.0);
+        camera.update(SETTLE);
+        assert_near(camera.position(), Vec3::new(0.0, 0.0, MAX_DISTANCE));
+    }
+
+    #[test]
+    fn input_is_smoothed_over_several_frames() {
+        let mut camera = OrbitCamera::new(Vec3::new(0.0, 0.0, 100.0), Vec3::ZERO);
+        camera.zoom(1.0);
+        let mut last = camera.current.distance;
+        assert_eq!(last, 100.0);
+        for _ in 0..3 {
+            camera.update(Duration::from_millis(16));
+            let distance = camera.current.distance;
+            assert!(distance < last && distance > 90.0);
+            last = distance;
+        }
+    }
+
+    #[test]
+    fn flying_covers_speed_times_elapsed() {
+        let mut camera = FlyCamera::new(Vec3::ZERO, Vec3::Z);
+        camera.update(Vec3::Z, false, Duration::from_secs(2));
+        assert_near(camera.position, Vec3::new(0.0, 0.0, 2.0 * FLY_SPEED));
+
+        camera.update(-Vec3::Z, true, Duration::from_secs(1));
+        assert_near(
+            camera.position,
+            Vec3::new(0.0, 0.0, (2.0 - SPRINT_MULTIPLIER) * FLY_SPEED),
+        );
+    }
+
+    #[test]
+    fn flying_diagonally_is_no_faster() {
+        let mut camera = FlyCamera::new(Vec3::ZERO, Vec3::Z);
+        camera.update(Vec3::new(1.0, 0.0, 1.0), false, Duration::from_secs(1));
+        assert!((camera.position.length() - FLY_SPEED).abs() < 1e-3);
+        // Screen right is world -X, as the projection flips X
+        assert!(camera.position.x < 0.0 && camera.position.z > 0.0);
+    }
+
+    #[test]
+    fn flying_slowly_with_a_stick() {
+        let mut camera = FlyCamera::new(Vec3::ZERO, Vec3::Z);
+        camera.update(Vec3::new(0.0, 0.0, 0.25), false, Duration::from_secs(1));
+        assert_near(camera.position, Vec3::new(0.0, 0.0, 0.25 * FLY_SPEED));
+    }
+
+    #[test]
+    fn toggling_keeps_the_view() {
+        let position = Vec3::new(0.0, 50.0, -50.0);
+        let mut camera = Camera::Orbit(OrbitCamera::new(position, Vec3::ZERO));
+        let view = camera.view();
+        camera.toggle();
+        assert!(matches!(camera, Camera::Fly(_)));
+        assert!(camera.view().abs_diff_eq(view, 1e-4));
+        camera.toggle();
+        assert!(matches!(camera, Camera::Orbit(_)));
+        assert!(camera.view().abs_diff_eq(view, 1e-4));
+    }
+}
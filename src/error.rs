@@ -1,3 +1,5 @@
+use std::str::Utf8Error;
+
 use sdl3_sys::error::SDL_GetError;
 
 use crate::util::null_terminated_sdl_str;
@@ -29,6 +31,22 @@ impl SdlError {
     }
 }
 
+impl From<Utf8Error> for SdlError {
+    fn from(error: Utf8Error) -> Self {
+        Self {
+            message: format!("Utf8Error: {}", error),
+        }
+    }
+}
+
+impl From<std::io::Error> for SdlError {
+    fn from(error: std::io::Error) -> Self {
+        Self {
+            message: format!("IoError: {}", error),
+        }
+    }
+}
+
 pub trait SdlFunctionResult<T> {
     fn ok(self) -> SdlResult<T>;
 }
@@ -0,0 +1,50 @@
+use crate::{error::SdlResult, sdl::Fence};
+
+struct FrameSlot<'a, T> {
+    fence: Option<Fence<'a>>,
+    resources: T,
+}
+
+/// Lets the CPU record up to N frames ahead of the GPU. Each frame slot has
+/// its own resources and the fence of the last frame submitted from it, and
+/// we only block when a slot comes around again before the GPU is done with
+/// it.
+pub struct FrameScheduler<'a, T> {
+    slots: Vec<FrameSlot<'a, T>>,
+    current: usize,
+}
+
+impl<'a, T> FrameScheduler<'a, T> {
+    pub fn new<F: FnMut(usize) -> SdlResult<T>>(
+        frames_in_flight: usize,
+        mut create_resources: F,
+    ) -> SdlResult<Self> {
+        assert!(frames_in_flight > 0);
+        let slots = (0..frames_in_flight)
+            .map(|index| {
+                Ok(FrameSlot {
+                    fence: None,
+                    resources: create_resources(index)?,
+                })
+            })
+            .collect::<SdlResult<_>>()?;
+        Ok(Self { slots, current: 0 })
+    }
+
+    /// Waits for the GPU to finish the last frame that used the current slot
+    /// and returns the slot's resources for recording the next one.
+    pub fn begin_frame(&mut self) -> SdlResult<&mut T> {
+        let slot = &mut self.slots[self.current];
+        if let Some(fence) = slot.fence.take() {
+            fence.wait()?;
+        }
+        Ok(&mut slot.resources)
+    }
+
+    /// Records the fence for the frame just submitted and moves to the next
+    /// slot.
+    pub fn end_frame(&mut self, fence: Fence<'a>) {
+        self.slots[self.current].fence = Some(fence);
+        self.current = (self.current + 1) % self.slots.len();
+    }
+}
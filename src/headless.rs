@@ -0,0 +1,112 @@
+use std::time::Duration;
+
+use glam::{Mat4, Vec3};
+use sdl3_sys::{
+    gpu::{
+        SDL_GPUSampleCount, SDL_GPUTextureCreateInfo, SDL_GPU_TEXTUREFORMAT_R8G8B8A8_UNORM,
+        SDL_GPU_TEXTURETYPE_2D, SDL_GPU_TEXTUREUSAGE_COLOR_TARGET,
+        SDL_GPU_TRANSFERBUFFERUSAGE_DOWNLOAD,
+    },
+    init::SDL_INIT_VIDEO,
+};
+
+use crate::{
+    camera::OrbitCamera,
+    cube_mesh,
+    error::{SdlError, SdlResult},
+    options::Options,
+    renderer::Renderer,
+    sdl::{GpuDevice, RenderTexture, SdlContext},
+    shaders, CAMERA_POSITION, ROTATION_SPEED,
+};
+
+/// How far time moves on between frames. It's fixed, rather than measured,
+/// so that the same options always produce the same images.
+const FRAME_TIME: Duration = Duration::from_millis(16);
+/// What we exit with when there's no GPU to render with, even a software
+/// one, so that tests can tell that apart from rendering going wrong.
+const NO_DEVICE_EXIT_CODE: i32 = 3;
+
+/// Renders frames into an offscreen texture and writes each one to an image
+/// file, without needing a window or a display.
+pub fn run(options: &Options) -> SdlResult<()> {
+    // Init SDL. The video subsystem is still what loads Vulkan, even though
+    // there's no window.
+    let _sdl = SdlContext::init(SDL_INIT_VIDEO).unwrap_or_else(|error| no_device(error));
+
+    // Init GPU
+    let shader_formats = shaders::shader::VS_MAIN.formats() & shaders::shader::FS_MAIN.formats();
+    let device = GpuDevice::new(shader_formats, true, options.backend)
+        .unwrap_or_else(|error| no_device(error));
+
+    // Log the backend
+    println!("GPU backend: {}", device.driver()?);
+
+    // Create the texture we render into, and a transfer buffer to read it
+    // back through. RGBA8 is always supported as a color target and is
+    // what the image formats want anyway.
+    let (width, height) = options.size;
+    let color_format = SDL_GPU_TEXTUREFORMAT_R8G8B8A8_UNORM;
+    let target = device.create_texture(&SDL_GPUTextureCreateInfo {
+        r#type: SDL_GPU_TEXTURETYPE_2D,
+        format: color_format,
+        usage: SDL_GPU_TEXTUREUSAGE_COLOR_TARGET,
+        width,
+        height,
+        layer_count_or_depth: 1,
+        num_levels: 1,
+        sample_count: SDL_GPUSampleCount::_1,
+        props: 0,
+    })?;
+    let mut download_buffer =
+        device.create_transfer_buffer(SDL_GPU_TRANSFERBUFFERUSAGE_DOWNLOAD, width * height * 4)?;
+
+    let mut renderer = Renderer::new(&device, color_format, options.depth, options.sample_count)?;
+    let (cube_vertices, cube_indices) = cube_mesh();
+    renderer.set_mesh(&cube_vertices, &cube_indices);
+    let camera = OrbitCamera::new(CAMERA_POSITION, Vec3::ZERO);
+    let mut current_rotation = options.rotation;
+
+    std::fs::create_dir_all(&options.output)?;
+    for index in 0..options.frames {
+        let local_transform = Mat4::from_rotation_y(current_rotation.to_radians());
+
+        // Draw, then copy the result back once the render pass is done
+        let mut command_buffer = device.acquire_command_buffer()?;
+        renderer.draw(
+            &mut command_buffer,
+            RenderTexture::Texture(&target),
+            camera.view(),
+            local_transform,
+        )?;
+        command_buffer
+            .begin_copy_pass()?
+            .download_from_texture(&target, &download_buffer, 0);
+
+        // Unlike the windowed path there's nothing to overlap with, so wait
+        // for the frame right away
+        let fence = command_buffer.submit_and_acquire_fence()?;
+        fence.wait()?;
+        renderer.end_frame(fence);
+
+        let path = options.output.join(format!(
+            "frame{:04}.{}",
+            index,
+            options.image_format.extension()
+        ));
+        options
+            .image_format
+            .write(&path, width, height, &download_buffer.map(false)?)?;
+        println!("Wrote {}", path.display());
+
+        current_rotation =
+            (current_rotation + (ROTATION_SPEED * FRAME_TIME.as_millis() as f32)) % 360.0;
+    }
+
+    Ok(())
+}
+
+fn no_device(error: SdlError) -> ! {
+    eprintln!("Couldn't create a GPU device: {}", error);
+    std::process::exit(NO_DEVICE_EXIT_CODE)
+}
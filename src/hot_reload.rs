@@ -0,0 +1,149 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use sdl3_sys::gpu::{
+    SDL_GPUShaderFormat, SDL_GPU_SHADERFORMAT_MSL, SDL_GPU_SHADERFORMAT_SPIRV,
+    SDL_GPU_SHADERSTAGE_VERTEX,
+};
+
+use shader_compiler::{EntryPoint, ShaderSource, ShaderStage};
+
+use crate::{
+    sdl::{GpuDevice, SdlGpuShader},
+    shader::{EmbeddedShader, ShaderMetadata},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Notices when anything under a directory is modified, by polling.
+pub struct ShaderWatcher {
+    dir: PathBuf,
+    last_modified: SystemTime,
+    last_poll: Instant,
+}
+
+impl ShaderWatcher {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        let dir = dir.as_ref().to_owned();
+        Self {
+            last_modified: newest_modification(&dir),
+            dir,
+            last_poll: Instant::now(),
+        }
+    }
+
+    /// Returns true if something was modified since the last time this
+    /// returned true. Cheap to call every frame, as it only looks at the
+    /// files every so often.
+    pub fn poll(&mut self) -> bool {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return false;
+        }
+        self.last_poll = Instant::now();
+        let modified = newest_modification(&self.dir);
+        if modified > self.last_modified {
+            self.last_modified = modified;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+fn newest_modification(dir: &Path) -> SystemTime {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return SystemTime::UNIX_EPOCH;
+    };
+    entries
+        .flatten()
+        .map(|entry| {
+            let path = entry.path();
+            if path.is_dir() {
+                newest_modification(&path)
+            } else {
+                entry
+                    .metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .unwrap_or(SystemTime::UNIX_EPOCH)
+            }
+        })
+        .max()
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+/// Recompiles an embedded shader from its current source, for whichever of
+/// the formats naga can produce the device takes.
+fn recompile(
+    shader: &EmbeddedShader,
+    device_formats: SDL_GPUShaderFormat,
+) -> Result<(ShaderMetadata, SDL_GPUShaderFormat, Vec<u8>), String> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(shader.source);
+    let source = ShaderSource::read(path)?;
+    let module = source.parse()?;
+    let entrypoint = shader.metadata.entrypoint.to_str().unwrap();
+    let stage = if shader.metadata.stage == SDL_GPU_SHADERSTAGE_VERTEX {
+        ShaderStage::Vertex
+    } else {
+        ShaderStage::Fragment
+    };
+    let entry_point = EntryPoint::new(&source, module, entrypoint, stage)?;
+
+    let (format, code) = if device_formats & SDL_GPU_SHADERFORMAT_SPIRV != 0 {
+        (SDL_GPU_SHADERFORMAT_SPIRV, entry_point.to_spv()?)
+    } else if device_formats & SDL_GPU_SHADERFORMAT_MSL != 0 {
+        (SDL_GPU_SHADERFORMAT_MSL, entry_point.to_msl()?.into_bytes())
+    } else {
+        return Err("Reloading shaders needs a device that takes SPIR-V or MSL".to_owned());
+    };
+
+    let resources = &entry_point.resources;
+    let metadata = ShaderMetadata {
+        entrypoint: shader.metadata.entrypoint,
+        stage: shader.metadata.stage,
+        num_samplers: resources.num_samplers,
+        num_storage_textures: resources.num_storage_textures,
+        num_storage_buffers: resources.num_storage_buffers,
+        num_uniform_buffers: resources.num_uniform_buffers,
+    };
+    Ok((metadata, format, code))
+}
+
+/// Recompiles an embedded shader from its current source and creates it on
+/// the device.
+pub fn reload_shader<'a>(
+    device: &'a GpuDevice,
+    shader: &EmbeddedShader,
+) -> Result<SdlGpuShader<'a>, String> {
+    let (metadata, format, code) = recompile(shader, device.shader_formats())?;
+    device
+        .create_shader(&metadata.shader_info(&code, format))
+        .map_err(|error| error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shaders;
+
+    #[test]
+    fn recompiling_matches_the_embedded_shader() {
+        for shader in [&shaders::shader::VS_MAIN, &shaders::shader::FS_MAIN] {
+            let (metadata, format, code) = recompile(shader, SDL_GPU_SHADERFORMAT_SPIRV).unwrap();
+            assert_eq!(format, SDL_GPU_SHADERFORMAT_SPIRV);
+            assert_eq!(metadata.entrypoint, shader.metadata.entrypoint);
+            assert_eq!(
+                metadata.num_uniform_buffers,
+                shader.metadata.num_uniform_buffers
+            );
+            let embedded = shader
+                .code
+                .iter()
+                .find(|(format, _)| *format == SDL_GPU_SHADERFORMAT_SPIRV)
+                .unwrap()
+                .1;
+            assert_eq!(code, embedded);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use std::path::Path;
+
+/// The file formats frames can be written in. Both are simple enough to write
+/// without pulling in an image crate.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Ppm,
+}
+
+impl ImageFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Ppm => "ppm",
+        }
+    }
+
+    /// Encodes tightly packed 8-bit RGBA pixels, top row first. PPM has no
+    /// alpha channel, so it's dropped there.
+    pub fn encode(self, width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+        assert_eq!(rgba.len(), width as usize * height as usize * 4);
+        match self {
+            ImageFormat::Png => encode_png(width, height, rgba),
+            ImageFormat::Ppm => encode_ppm(width, height, rgba),
+        }
+    }
+
+    pub fn write<P: AsRef<Path>>(
+        self,
+        path: P,
+        width: u32,
+        height: u32,
+        rgba: &[u8],
+    ) -> std::io::Result<()> {
+        std::fs::write(path, self.encode(width, height, rgba))
+    }
+}
+
+fn encode_ppm(width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+    let mut data = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+    for pixel in rgba.chunks_exact(4) {
+        data.extend_from_slice(&pixel[..3]);
+    }
+    data
+}
+
+/// Writes the pixels uncompressed, as stored deflate blocks. The files are
+/// bigger than they need to be, but any PNG reader can open them.
+fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+    // Every row starts with its filter type, which is always none here
+    let row_size = width as usize * 4;
+    let mut scanlines = Vec::with_capacity((row_size + 1) * height as usize);
+    for row in rgba.chunks_exact(row_size.max(1)) {
+        scanlines.push(0);
+        scanlines.extend_from_slice(row);
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // 8 bits per channel, RGBA, default compression, filtering and no
+    // interlacing
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut data = b"\x89PNG\r\n\x1a\n".to_vec();
+    write_chunk(&mut data, b"IHDR", &header);
+    write_chunk(&mut data, b"IDAT", &zlib_stored(&scanlines));
+    write_chunk(&mut data, b"IEND", &[]);
+    data
+}
+
+fn write_chunk(data: &mut Vec<u8>, kind: &[u8; 4], contents: &[u8]) {
+    data.extend_from_slice(&(contents.len() as u32).to_be_bytes());
+    let start = data.len();
+    data.extend_from_slice(kind);
+    data.extend_from_slice(contents);
+    let crc = crc32(&data[start..]);
+    data.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// A zlib stream made of uncompressed deflate blocks.
+fn zlib_stored(bytes: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = u16::MAX as usize;
+    let mut data = vec![0x78, 0x01];
+    let mut blocks = bytes.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        // Even an empty stream needs a final block
+        data.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        data.push(last as u8);
+        data.extend_from_slice(&len.to_le_bytes());
+        data.extend_from_slice(&(!len).to_le_bytes());
+        data.extend_from_slice(block);
+    }
+    data.extend_from_slice(&adler32(bytes).to_be_bytes());
+    data
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in bytes {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksums_match_known_values() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn ppm_drops_alpha() {
+        let rgba = [1, 2, 3, 255, 4, 5, 6, 0];
+        assert_eq!(
+            ImageFormat::Ppm.encode(2, 1, &rgba),
+            b"P6\n2 1\n255\n\x01\x02\x03\x04\x05\x06"
+        );
+    }
+
+    #[test]
+    fn png_has_header_and_image_end() {
+        let png = ImageFormat::Png.encode(2, 3, &[0x80; 2 * 3 * 4]);
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x02\0\0\0\x03"));
+        assert!(png.ends_with(b"\0\0\0\0IEND\xae\x42\x60\x82"));
+    }
+
+    #[test]
+    fn stored_blocks_split_large_images() {
+        let bytes = vec![7; 70_000];
+        let stream = zlib_stored(&bytes);
+        // Header, two blocks of header plus contents and the checksum
+        assert_eq!(stream.len(), 2 + (5 + 65535) + (5 + 4465) + 4);
+        assert_eq!(stream[2], 0);
+        assert_eq!(stream[2 + 5 + 65535], 1);
+    }
+}
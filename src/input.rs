@@ -0,0 +1,597 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use glam::Vec2;
+use sdl3_sys::{
+    gamepad::{
+        SDL_GamepadAxis, SDL_GamepadButton, SDL_GAMEPAD_BUTTON_BACK, SDL_GAMEPAD_BUTTON_DPAD_DOWN,
+        SDL_GAMEPAD_BUTTON_DPAD_LEFT, SDL_GAMEPAD_BUTTON_DPAD_RIGHT, SDL_GAMEPAD_BUTTON_DPAD_UP,
+        SDL_GAMEPAD_BUTTON_EAST, SDL_GAMEPAD_BUTTON_LEFT_SHOULDER, SDL_GAMEPAD_BUTTON_LEFT_STICK,
+        SDL_GAMEPAD_BUTTON_NORTH, SDL_GAMEPAD_BUTTON_RIGHT_SHOULDER,
+        SDL_GAMEPAD_BUTTON_RIGHT_STICK, SDL_GAMEPAD_BUTTON_SOUTH, SDL_GAMEPAD_BUTTON_START,
+        SDL_GAMEPAD_BUTTON_WEST,
+    },
+    keycode::{
+        SDL_Keycode, SDLK_BACKSPACE, SDLK_DOWN, SDLK_ESCAPE, SDLK_F1, SDLK_F10, SDLK_F11, SDLK_F12,
+        SDLK_F2, SDLK_F3, SDLK_F4, SDLK_F5, SDLK_F6, SDLK_F7, SDLK_F8, SDLK_F9, SDLK_LALT,
+        SDLK_LCTRL, SDLK_LEFT, SDLK_LSHIFT, SDLK_RALT, SDLK_RCTRL, SDLK_RETURN, SDLK_RIGHT,
+        SDLK_RSHIFT, SDLK_SPACE, SDLK_TAB, SDLK_UP,
+    },
+    mouse::{SDL_BUTTON_LEFT, SDL_BUTTON_MIDDLE, SDL_BUTTON_RIGHT},
+};
+
+use crate::sdl::Event;
+
+/// How far a stick has to move, out of 1, before it counts. Worn sticks don't
+/// quite return to the center.
+const STICK_DEADZONE: f32 = 0.15;
+const TRIGGER_DEADZONE: f32 = 0.05;
+/// Sticks and triggers respond to the square of how far they're pushed past
+/// the deadzone, for finer control near the center.
+const RESPONSE_EXPONENT: f32 = 2.0;
+
+macro_rules! actions {
+    ($($(#[$meta:meta])* $action:ident),* $(,)?) => {
+        /// Something the user can do, independent of which inputs do it.
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum Action {
+            $($(#[$meta])* $action),*
+        }
+
+        impl Action {
+            pub const ALL: &'static [Action] = &[$(Action::$action),*];
+
+            /// The name used for the action in bindings files.
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Action::$action => stringify!($action)),*
+                }
+            }
+        }
+    };
+}
+
+actions!(
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    Sprint,
+    /// Orbiting follows the mouse while this is held.
+    Drag,
+    PanPositiveX,
+    PanNegativeX,
+    PanPositiveY,
+    PanNegativeY,
+    PanPositiveZ,
+    PanNegativeZ,
+    ToggleCamera,
+    ToggleWireframe,
+);
+
+/// A button that can be bound to an action.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Input {
+    Key(SDL_Keycode),
+    MouseButton(u8),
+    GamepadButton(SDL_GamepadButton),
+}
+
+/// Names for the keys that aren't a single letter or digit.
+const KEY_NAMES: &[(&str, SDL_Keycode)] = &[
+    ("Space", SDLK_SPACE),
+    ("Tab", SDLK_TAB),
+    ("Return", SDLK_RETURN),
+    ("Escape", SDLK_ESCAPE),
+    ("Backspace", SDLK_BACKSPACE),
+    ("Up", SDLK_UP),
+    ("Down", SDLK_DOWN),
+    ("Left", SDLK_LEFT),
+    ("Right", SDLK_RIGHT),
+    ("LShift", SDLK_LSHIFT),
+    ("RShift", SDLK_RSHIFT),
+    ("LCtrl", SDLK_LCTRL),
+    ("RCtrl", SDLK_RCTRL),
+    ("LAlt", SDLK_LALT),
+    ("RAlt", SDLK_RALT),
+    ("F1", SDLK_F1),
+    ("F2", SDLK_F2),
+    ("F3", SDLK_F3),
+    ("F4", SDLK_F4),
+    ("F5", SDLK_F5),
+    ("F6", SDLK_F6),
+    ("F7", SDLK_F7),
+    ("F8", SDLK_F8),
+    ("F9", SDLK_F9),
+    ("F10", SDLK_F10),
+    ("F11", SDLK_F11),
+    ("F12", SDLK_F12),
+];
+
+const MOUSE_BUTTON_NAMES: &[(&str, i32)] = &[
+    ("MouseLeft", SDL_BUTTON_LEFT),
+    ("MouseMiddle", SDL_BUTTON_MIDDLE),
+    ("MouseRight", SDL_BUTTON_RIGHT),
+];
+
+/// Gamepad buttons are named by position rather than label, as the labels
+/// differ between controllers.
+const GAMEPAD_BUTTON_NAMES: &[(&str, SDL_GamepadButton)] = &[
+    ("GamepadSouth", SDL_GAMEPAD_BUTTON_SOUTH),
+    ("GamepadEast", SDL_GAMEPAD_BUTTON_EAST),
+    ("GamepadWest", SDL_GAMEPAD_BUTTON_WEST),
+    ("GamepadNorth", SDL_GAMEPAD_BUTTON_NORTH),
+    ("GamepadBack", SDL_GAMEPAD_BUTTON_BACK),
+    ("GamepadStart", SDL_GAMEPAD_BUTTON_START),
+    ("GamepadLeftStick", SDL_GAMEPAD_BUTTON_LEFT_STICK),
+    ("GamepadRightStick", SDL_GAMEPAD_BUTTON_RIGHT_STICK),
+    ("GamepadLeftShoulder", SDL_GAMEPAD_BUTTON_LEFT_SHOULDER),
+    ("GamepadRightShoulder", SDL_GAMEPAD_BUTTON_RIGHT_SHOULDER),
+    ("GamepadDPadUp", SDL_GAMEPAD_BUTTON_DPAD_UP),
+    ("GamepadDPadDown", SDL_GAMEPAD_BUTTON_DPAD_DOWN),
+    ("GamepadDPadLeft", SDL_GAMEPAD_BUTTON_DPAD_LEFT),
+    ("GamepadDPadRight", SDL_GAMEPAD_BUTTON_DPAD_RIGHT),
+];
+
+impl Input {
+    /// Parses the name of an input, ignoring case: a letter or digit for its
+    /// key, or one of the names in the tables above.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let mut chars = name.chars();
+        match (chars.next(), chars.next()) {
+            // Keycodes for printable keys are their unshifted character
+            (Some(c), None) if c.is_ascii_alphanumeric() => {
+                Some(Input::Key(c.to_ascii_lowercase() as SDL_Keycode))
+            }
+            _ => lookup(KEY_NAMES, name)
+                .map(Input::Key)
+                .or_else(|| {
+                    lookup(MOUSE_BUTTON_NAMES, name).map(|button| Input::MouseButton(button as u8))
+                })
+                .or_else(|| lookup(GAMEPAD_BUTTON_NAMES, name).map(Input::GamepadButton)),
+        }
+    }
+}
+
+fn lookup<T: Copy>(table: &[(&str, T)], name: &str) -> Option<T> {
+    table
+        .iter()
+        .find(|(table_name, _)| table_name.eq_ignore_ascii_case(name))
+        .map(|(_, value)| *value)
+}
+
+/// Which inputs trigger each action.
+pub struct Bindings(HashMap<Action, Vec<Input>>);
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use Action::*;
+        let bindings = [
+            (MoveForward, &["W"][..]),
+            (MoveBack, &["S"]),
+            (MoveLeft, &["A"]),
+            (MoveRight, &["D"]),
+            (MoveUp, &["E", "GamepadRightShoulder"]),
+            (MoveDown, &["Q", "GamepadLeftShoulder"]),
+            (Sprint, &["LShift", "GamepadLeftStick"]),
+            (Drag, &["MouseLeft"]),
+            (PanPositiveX, &["Q", "GamepadDPadRight"]),
+            (PanNegativeX, &["A", "GamepadDPadLeft"]),
+            (PanPositiveY, &["W", "GamepadDPadUp"]),
+            (PanNegativeY, &["S", "GamepadDPadDown"]),
+            (PanPositiveZ, &["E"]),
+            (PanNegativeZ, &["D"]),
+            (ToggleCamera, &["Tab", "GamepadBack"]),
+            (ToggleWireframe, &["F", "GamepadNorth"]),
+        ];
+        Self(
+            bindings
+                .into_iter()
+                .map(|(action, names)| {
+                    let inputs = names
+                        .iter()
+                        .map(|name| Input::from_name(name).expect("default bindings are valid"))
+                        .collect();
+                    (action, inputs)
+                })
+                .collect(),
+        )
+    }
+}
+
+impl Bindings {
+    /// Reads bindings from a file, on top of the defaults.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|error| format!("Couldn't read {}: {}", path.display(), error))?;
+        let mut bindings = Self::default();
+        bindings.parse(&text, path)?;
+        Ok(bindings)
+    }
+
+    /// Applies lines of `Action = Input, Input, ...`. An action that's listed
+    /// loses its previous bindings, and can be left with none by listing no
+    /// inputs. `#` starts a comment.
+    fn parse(&mut self, text: &str, path: &Path) -> Result<(), String> {
+        for (index, line) in text.lines().enumerate() {
+            let error = |message: String| format!("{}:{}: {}", path.display(), index + 1, message);
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (action_name, inputs) = line
+                .split_once('=')
+                .ok_or_else(|| error("expected `Action = Input, ...`".to_owned()))?;
+            let action_name = action_name.trim();
+            let action = *Action::ALL
+                .iter()
+                .find(|action| action.name().eq_ignore_ascii_case(action_name))
+                .ok_or_else(|| error(format!("unknown action '{}'", action_name)))?;
+            let inputs = inputs
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(|name| {
+                    Input::from_name(name).ok_or_else(|| error(format!("unknown input '{}'", name)))
+                })
+                .collect::<Result<_, _>>()?;
+            self.0.insert(action, inputs);
+        }
+        Ok(())
+    }
+
+    fn inputs(&self, action: Action) -> &[Input] {
+        self.0.get(&action).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Turns events into the state of each action, so that the main loop doesn't
+/// need to know what they're bound to.
+pub struct InputMap {
+    bindings: Bindings,
+    held: HashSet<Input>,
+    /// Actions that were triggered since the start of the frame.
+    pressed: HashSet<Action>,
+    mouse_motion: Vec2,
+    wheel: f32,
+    /// The raw position of each gamepad stick and trigger axis, out of 1.
+    gamepad_axes: HashMap<SDL_GamepadAxis, f32>,
+}
+
+impl InputMap {
+    pub fn new(bindings: Bindings) -> Self {
+        Self {
+            bindings,
+            held: HashSet::new(),
+            pressed: HashSet::new(),
+            mouse_motion: Vec2::ZERO,
+            wheel: 0.0,
+            gamepad_axes: HashMap::new(),
+        }
+    }
+
+    /// Forgets this frame's presses and movement. Call before polling the
+    /// next frame's events.
+    pub fn begin_frame(&mut self) {
+        self.pressed.clear();
+        self.mouse_motion = Vec2::ZERO;
+        self.wheel = 0.0;
+    }
+
+    pub fn handle_event(&mut self, event: &Event) {
+        match *event {
+            Event::KeyDown(key) => self.press(Input::Key(key)),
+            Event::KeyUp(key) => self.release(Input::Key(key)),
+            Event::MouseButtonDown(button) => self.press(Input::MouseButton(button)),
+            Event::MouseButtonUp(button) => self.release(Input::MouseButton(button)),
+            Event::GamepadButtonDown(button) => self.press(Input::GamepadButton(button)),
+            Event::GamepadButtonUp(button) => self.release(Input::GamepadButton(button)),
+            Event::MouseMotion { xrel, yrel } => self.mouse_motion += Vec2::new(xrel, yrel),
+            Event::MouseWheel(notches) => self.wheel += notches,
+            Event::GamepadAxis { axis, value } => {
+                self.gamepad_axes
+                    .insert(axis, (value as f32 / i16::MAX as f32).max(-1.0));
+            }
+            // Without this, anything held on the gamepad would stay held.
+            // Simpler than tracking which gamepad each input came from, at
+            // the cost of unplugging one also releasing the others.
+            Event::GamepadRemoved(_) => {
+                self.held
+                    .retain(|input| !matches!(input, Input::GamepadButton(_)));
+                self.gamepad_axes.clear();
+            }
+            _ => {}
+        }
+    }
+
+    fn press(&mut self, input: Input) {
+        if self.held.insert(input) {
+            for action in Action::ALL {
+                if self.bindings.inputs(*action).contains(&input) {
+                    self.pressed.insert(*action);
+                }
+            }
+        }
+    }
+
+    fn release(&mut self, input: Input) {
+        self.held.remove(&input);
+    }
+
+    /// Whether any of the action's inputs are held down.
+    pub fn is_held(&self, action: Action) -> bool {
+        self.bindings
+            .inputs(action)
+            .iter()
+            .any(|input| self.held.contains(input))
+    }
+
+    /// Whether one of the action's inputs went down this frame.
+    pub fn was_pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    /// 1 if `positive` is held, -1 if `negative` is, 0 for both or neither.
+    pub fn axis(&self, positive: Action, negative: Action) -> f32 {
+        let value = |action| if self.is_held(action) { 1.0 } else { 0.0 };
+        value(positive) - value(negative)
+    }
+
+    /// How far the mouse moved this frame, in pixels.
+    pub fn mouse_motion(&self) -> Vec2 {
+        self.mouse_motion
+    }
+
+    /// Wheel notches scrolled this frame, positive away from the user.
+    pub fn wheel(&self) -> f32 {
+        self.wheel
+    }
+
+    fn gamepad_axis(&self, axis: SDL_GamepadAxis) -> f32 {
+        self.gamepad_axes.get(&axis).copied().unwrap_or(0.0)
+    }
+
+    /// The position of a gamepad stick, given its two axes, with the
+    /// deadzone and response curve applied. The result is at most 1 long
+    /// and, as with SDL's axes, +Y is down.
+    pub fn stick(&self, x: SDL_GamepadAxis, y: SDL_GamepadAxis) -> Vec2 {
+        let stick = Vec2::new(self.gamepad_axis(x), self.gamepad_axis(y));
+        let length = stick.length();
+        if length == 0.0 {
+            Vec2::ZERO
+        } else {
+            // The deadzone is a circle rather than a square, so that
+            // diagonals don't get cut off
+            stick / length * response(length, STICK_DEADZONE)
+        }
+    }
+
+    /// How far a trigger is pulled, from 0 to 1, with the deadzone and
+    /// response curve applied.
+    pub fn trigger(&self, axis: SDL_GamepadAxis) -> f32 {
+        response(self.gamepad_axis(axis), TRIGGER_DEADZONE)
+    }
+}
+
+/// Maps how far an input is pushed to how much it should do: nothing inside
+/// the deadzone, then rising from 0 to 1 along the response curve.
+fn response(magnitude: f32, deadzone: f32) -> f32 {
+    if magnitude <= deadzone {
+        0.0
+    } else {
+        ((magnitude - deadzone) / (1.0 - deadzone))
+            .min(1.0)
+            .powf(RESPONSE_EXPONENT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl3_sys::{
+        events::{
+            SDL_Event, SDL_EVENT_GAMEPAD_AXIS_MOTION, SDL_EVENT_GAMEPAD_BUTTON_DOWN,
+            SDL_EVENT_GAMEPAD_REMOVED, SDL_EVENT_KEY_DOWN, SDL_EVENT_KEY_UP,
+            SDL_EVENT_MOUSE_BUTTON_DOWN, SDL_EVENT_MOUSE_MOTION,
+        },
+        gamepad::{SDL_GAMEPAD_AXIS_LEFTX, SDL_GAMEPAD_AXIS_LEFTY, SDL_GAMEPAD_AXIS_RIGHT_TRIGGER},
+        keycode::{SDLK_F, SDLK_W},
+    };
+
+    use super::*;
+
+    fn event(fill: impl FnOnce(&mut SDL_Event)) -> Event {
+        let mut event = SDL_Event {
+            padding: [0u8; 128],
+        };
+        fill(&mut event);
+        Event::from(&event)
+    }
+
+    fn key(key: SDL_Keycode, down: bool) -> Event {
+        event(|event| {
+            event.key.r#type = if down {
+                SDL_EVENT_KEY_DOWN
+            } else {
+                SDL_EVENT_KEY_UP
+            };
+            event.key.key = key;
+            event.key.down = down;
+        })
+    }
+
+    fn gamepad_axis(axis: SDL_GamepadAxis, value: i16) -> Event {
+        event(|event| {
+            event.gaxis.r#type = SDL_EVENT_GAMEPAD_AXIS_MOTION;
+            event.gaxis.axis = axis.0 as u8;
+            event.gaxis.value = value;
+        })
+    }
+
+    #[test]
+    fn held_keys_drive_actions_until_released() {
+        let mut input = InputMap::new(Bindings::default());
+        input.handle_event(&key(SDLK_W, true));
+        assert!(input.is_held(Action::MoveForward));
+        assert!(input.was_pressed(Action::MoveForward));
+        assert_eq!(input.axis(Action::MoveForward, Action::MoveBack), 1.0);
+
+        // Still held next frame, but no longer newly pressed
+        input.begin_frame();
+        assert!(input.is_held(Action::MoveForward));
+        assert!(!input.was_pressed(Action::MoveForward));
+
+        input.handle_event(&key(SDLK_W, false));
+        assert!(!input.is_held(Action::MoveForward));
+        assert_eq!(input.axis(Action::MoveForward, Action::MoveBack), 0.0);
+    }
+
+    #[test]
+    fn key_repeats_are_not_presses() {
+        let mut input = InputMap::new(Bindings::default());
+        input.handle_event(&key(SDLK_F, true));
+        input.begin_frame();
+        input.handle_event(&event(|event| {
+            event.key.r#type = SDL_EVENT_KEY_DOWN;
+            event.key.key = SDLK_F;
+            event.key.down = true;
+            event.key.repeat = true;
+        }));
+        assert!(!input.was_pressed(Action::ToggleWireframe));
+    }
+
+    #[test]
+    fn mouse_buttons_and_motion() {
+        let mut input = InputMap::new(Bindings::default());
+        input.handle_event(&event(|event| {
+            event.button.r#type = SDL_EVENT_MOUSE_BUTTON_DOWN;
+            event.button.button = SDL_BUTTON_LEFT as u8;
+            event.button.down = true;
+        }));
+        for (xrel, yrel) in [(3.0, -1.0), (2.0, 4.0)] {
+            input.handle_event(&event(|event| {
+                event.motion.r#type = SDL_EVENT_MOUSE_MOTION;
+                event.motion.xrel = xrel;
+                event.motion.yrel = yrel;
+            }));
+        }
+        assert!(input.is_held(Action::Drag));
+        assert_eq!(input.mouse_motion(), Vec2::new(5.0, 3.0));
+        input.begin_frame();
+        assert_eq!(input.mouse_motion(), Vec2::ZERO);
+    }
+
+    #[test]
+    fn loaded_bindings_replace_the_defaults() {
+        let mut bindings = Bindings::default();
+        bindings
+            .parse(
+                "# Arrow keys instead of WASD\n\
+                 moveforward = Up, GamepadDPadUp\n\
+                 MoveBack=down\n\
+                 \n\
+                 ToggleWireframe =\n",
+                Path::new("bindings.cfg"),
+            )
+            .unwrap();
+        let mut input = InputMap::new(bindings);
+        input.handle_event(&key(SDLK_W, true));
+        assert!(!input.is_held(Action::MoveForward));
+        input.handle_event(&key(SDLK_UP, true));
+        assert!(input.is_held(Action::MoveForward));
+        input.handle_event(&key(SDLK_DOWN, true));
+        assert_eq!(input.axis(Action::MoveForward, Action::MoveBack), 0.0);
+        input.handle_event(&key(SDLK_F, true));
+        assert!(!input.was_pressed(Action::ToggleWireframe));
+        // Untouched actions keep their defaults
+        input.handle_event(&key(SDLK_TAB, true));
+        assert!(input.was_pressed(Action::ToggleCamera));
+    }
+
+    #[test]
+    fn bad_bindings_point_at_the_line() {
+        let mut bindings = Bindings::default();
+        let error = bindings
+            .parse("Sprint = LShift\nJump = Space\n", Path::new("bindings.cfg"))
+            .unwrap_err();
+        assert_eq!(error, "bindings.cfg:2: unknown action 'Jump'");
+        let error = bindings
+            .parse("Sprint = LShfit\n", Path::new("bindings.cfg"))
+            .unwrap_err();
+        assert_eq!(error, "bindings.cfg:1: unknown input 'LShfit'");
+    }
+
+    #[test]
+    fn shipped_bindings_match_the_defaults() {
+        // Parse on top of nothing, so that anything missing from the file
+        // shows up
+        let mut shipped = Bindings(HashMap::new());
+        let path = Path::new("data/bindings.cfg");
+        shipped
+            .parse(&std::fs::read_to_string(path).unwrap(), path)
+            .unwrap();
+        let defaults = Bindings::default();
+        for action in Action::ALL {
+            assert!(
+                shipped.inputs(*action) == defaults.inputs(*action),
+                "{:?}",
+                action
+            );
+        }
+    }
+
+    #[test]
+    fn sticks_have_a_deadzone_and_a_curve() {
+        let mut input = InputMap::new(Bindings::default());
+        let mut stick = |x: i16, y: i16| {
+            input.handle_event(&gamepad_axis(SDL_GAMEPAD_AXIS_LEFTX, x));
+            input.handle_event(&gamepad_axis(SDL_GAMEPAD_AXIS_LEFTY, y));
+            input.stick(SDL_GAMEPAD_AXIS_LEFTX, SDL_GAMEPAD_AXIS_LEFTY)
+        };
+        // Resting slightly off center
+        assert_eq!(stick(2000, -3000), Vec2::ZERO);
+        // All the way, in either direction, including diagonals
+        assert!((stick(i16::MAX, 0) - Vec2::X).length() < 1e-4);
+        assert!((stick(0, i16::MIN) + Vec2::Y).length() < 1e-4);
+        assert!((stick(i16::MAX, i16::MAX).length() - 1.0).abs() < 1e-4);
+        // Halfway is less than half, but still points the same way
+        let half = stick(-i16::MAX / 2, 0);
+        assert!(half.x < 0.0 && half.x > -0.5 && half.y == 0.0);
+    }
+
+    #[test]
+    fn triggers_rise_from_zero_to_one() {
+        let mut input = InputMap::new(Bindings::default());
+        assert_eq!(input.trigger(SDL_GAMEPAD_AXIS_RIGHT_TRIGGER), 0.0);
+        input.handle_event(&gamepad_axis(SDL_GAMEPAD_AXIS_RIGHT_TRIGGER, 1000));
+        assert_eq!(input.trigger(SDL_GAMEPAD_AXIS_RIGHT_TRIGGER), 0.0);
+        input.handle_event(&gamepad_axis(SDL_GAMEPAD_AXIS_RIGHT_TRIGGER, i16::MAX));
+        assert_eq!(input.trigger(SDL_GAMEPAD_AXIS_RIGHT_TRIGGER), 1.0);
+    }
+
+    #[test]
+    fn unplugging_a_gamepad_releases_it() {
+        let mut input = InputMap::new(Bindings::default());
+        input.handle_event(&event(|event| {
+            event.gbutton.r#type = SDL_EVENT_GAMEPAD_BUTTON_DOWN;
+            event.gbutton.button = SDL_GAMEPAD_BUTTON_LEFT_STICK.0 as u8;
+            event.gbutton.down = true;
+        }));
+        input.handle_event(&gamepad_axis(SDL_GAMEPAD_AXIS_LEFTY, i16::MIN));
+        assert!(input.is_held(Action::Sprint));
+        assert!(input.stick(SDL_GAMEPAD_AXIS_LEFTX, SDL_GAMEPAD_AXIS_LEFTY) != Vec2::ZERO);
+
+        input.handle_event(&event(|event| {
+            event.gdevice.r#type = SDL_EVENT_GAMEPAD_REMOVED;
+        }));
+        assert!(!input.is_held(Action::Sprint));
+        assert_eq!(
+            input.stick(SDL_GAMEPAD_AXIS_LEFTX, SDL_GAMEPAD_AXIS_LEFTY),
+            Vec2::ZERO
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use std::{ffi::CStr, path::PathBuf};
+
+use crate::{image::ImageFormat, targets::DepthSettings};
+
+use sdl3_sys::gpu::{
+    SDL_GPUCompareOp, SDL_GPUPresentMode, SDL_GPUSampleCount, SDL_GPUSwapchainComposition,
+    SDL_GPUTextureFormat, SDL_GPU_COMPAREOP_ALWAYS, SDL_GPU_COMPAREOP_EQUAL,
+    SDL_GPU_COMPAREOP_GREATER, SDL_GPU_COMPAREOP_GREATER_OR_EQUAL, SDL_GPU_COMPAREOP_LESS,
+    SDL_GPU_COMPAREOP_LESS_OR_EQUAL, SDL_GPU_COMPAREOP_NEVER, SDL_GPU_COMPAREOP_NOT_EQUAL,
+    SDL_GPU_PRESENTMODE_IMMEDIATE, SDL_GPU_PRESENTMODE_MAILBOX, SDL_GPU_PRESENTMODE_VSYNC,
+    SDL_GPU_SWAPCHAINCOMPOSITION_HDR10_ST2048, SDL_GPU_SWAPCHAINCOMPOSITION_HDR_EXTENDED_LINEAR,
+    SDL_GPU_SWAPCHAINCOMPOSITION_SDR, SDL_GPU_SWAPCHAINCOMPOSITION_SDR_LINEAR,
+    SDL_GPU_TEXTUREFORMAT_D16_UNORM, SDL_GPU_TEXTUREFORMAT_D24_UNORM,
+    SDL_GPU_TEXTUREFORMAT_D24_UNORM_S8_UINT, SDL_GPU_TEXTUREFORMAT_D32_FLOAT,
+    SDL_GPU_TEXTUREFORMAT_D32_FLOAT_S8_UINT,
+};
+
+const USAGE: &str = "Usage: sdl3cube [--msaa <1|2|4|8>] [--backend <vulkan|direct3d12|metal>]
+    [--bindings <file>] [--present-mode <vsync|immediate|mailbox>]
+    [--swapchain <sdr|sdr-linear|hdr-extended-linear|hdr10>] [--size <width>x<height>]
+    [--rotation <degrees>] [--headless] [--frames <count>] [--output <dir>]
+    [--image-format <png|ppm>] [--depth-format <d16|d24|d32|d24-s8|d32-s8>]
+    [--depth-compare <less|less-equal|greater|greater-equal|equal|not-equal|always|never>]";
+
+pub const PRESENT_MODES: &[(&str, SDL_GPUPresentMode)] = &[
+    ("vsync", SDL_GPU_PRESENTMODE_VSYNC),
+    ("immediate", SDL_GPU_PRESENTMODE_IMMEDIATE),
+    ("mailbox", SDL_GPU_PRESENTMODE_MAILBOX),
+];
+
+pub const SWAPCHAIN_COMPOSITIONS: &[(&str, SDL_GPUSwapchainComposition)] = &[
+    ("sdr", SDL_GPU_SWAPCHAINCOMPOSITION_SDR),
+    ("sdr-linear", SDL_GPU_SWAPCHAINCOMPOSITION_SDR_LINEAR),
+    (
+        "hdr-extended-linear",
+        SDL_GPU_SWAPCHAINCOMPOSITION_HDR_EXTENDED_LINEAR,
+    ),
+    ("hdr10", SDL_GPU_SWAPCHAINCOMPOSITION_HDR10_ST2048),
+];
+
+pub const DEPTH_FORMATS: &[(&str, SDL_GPUTextureFormat)] = &[
+    ("d16", SDL_GPU_TEXTUREFORMAT_D16_UNORM),
+    ("d24", SDL_GPU_TEXTUREFORMAT_D24_UNORM),
+    ("d32", SDL_GPU_TEXTUREFORMAT_D32_FLOAT),
+    ("d24-s8", SDL_GPU_TEXTUREFORMAT_D24_UNORM_S8_UINT),
+    ("d32-s8", SDL_GPU_TEXTUREFORMAT_D32_FLOAT_S8_UINT),
+];
+
+const DEPTH_COMPARE_OPS: &[(&str, SDL_GPUCompareOp)] = &[
+    ("less", SDL_GPU_COMPAREOP_LESS),
+    ("less-equal", SDL_GPU_COMPAREOP_LESS_OR_EQUAL),
+    ("greater", SDL_GPU_COMPAREOP_GREATER),
+    ("greater-equal", SDL_GPU_COMPAREOP_GREATER_OR_EQUAL),
+    ("equal", SDL_GPU_COMPAREOP_EQUAL),
+    ("not-equal", SDL_GPU_COMPAREOP_NOT_EQUAL),
+    ("always", SDL_GPU_COMPAREOP_ALWAYS),
+    ("never", SDL_GPU_COMPAREOP_NEVER),
+];
+
+/// The name an option value goes by on the command line.
+pub fn name_of<T: PartialEq>(table: &[(&'static str, T)], value: T) -> &'static str {
+    table
+        .iter()
+        .find(|(_, table_value)| *table_value == value)
+        .map_or("unknown", |(name, _)| name)
+}
+
+/// Settings chosen on the command line at startup.
+pub struct Options {
+    pub sample_count: SDL_GPUSampleCount,
+    /// The SDL GPU driver to use, instead of letting SDL pick.
+    pub backend: Option<&'static CStr>,
+    /// A file to load key bindings from, like data/bindings.cfg.
+    pub bindings: Option<PathBuf>,
+    pub present_mode: SDL_GPUPresentMode,
+    pub swapchain_composition: SDL_GPUSwapchainComposition,
+    /// The size of the window, or of the image when headless.
+    pub size: (u32, u32),
+    /// How far the cube has turned around Y at the start, in degrees.
+    pub rotation: f32,
+    /// Render to an offscreen texture and write frames to image files,
+    /// rather than opening a window.
+    pub headless: bool,
+    /// How many frames to write when headless.
+    pub frames: u32,
+    /// The directory to write frames to when headless.
+    pub output: PathBuf,
+    pub image_format: ImageFormat,
+    /// The depth buffer's format and how fragments are tested against it.
+    pub depth: DepthSettings,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            sample_count: SDL_GPUSampleCount::_4,
+            backend: None,
+            bindings: None,
+            present_mode: SDL_GPU_PRESENTMODE_VSYNC,
+            swapchain_composition: SDL_GPU_SWAPCHAINCOMPOSITION_SDR,
+            size: (640, 480),
+            rotation: 0.0,
+            headless: false,
+            frames: 1,
+            output: PathBuf::from("frames"),
+            image_format: ImageFormat::Png,
+            depth: DepthSettings::default(),
+        }
+    }
+}
+
+impl Options {
+    pub fn from_env() -> Result<Self, String> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Self, String> {
+        let mut options = Self::default();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {}\n{}", arg, USAGE))
+            };
+            match arg.as_str() {
+                "--msaa" => {
+                    options.sample_count = match value()?.as_str() {
+                        "1" => SDL_GPUSampleCount::_1,
+                        "2" => SDL_GPUSampleCount::_2,
+                        "4" => SDL_GPUSampleCount::_4,
+                        "8" => SDL_GPUSampleCount::_8,
+                        other => return Err(format!("Invalid sample count: {}\n{}", other, USAGE)),
+                    }
+                }
+                "--backend" => {
+                    options.backend = Some(match value()?.as_str() {
+                        "vulkan" => c"vulkan",
+                        "direct3d12" | "d3d12" => c"direct3d12",
+                        "metal" => c"metal",
+                        other => return Err(format!("Unknown backend: {}\n{}", other, USAGE)),
+                    })
+                }
+                "--bindings" => options.bindings = Some(value()?.into()),
+                "--present-mode" => {
+                    let value = value()?;
+                    options.present_mode = lookup(PRESENT_MODES, &value)
+                        .ok_or_else(|| format!("Unknown present mode: {}\n{}", value, USAGE))?
+                }
+                "--swapchain" => {
+                    let value = value()?;
+                    options.swapchain_composition = lookup(SWAPCHAIN_COMPOSITIONS, &value)
+                        .ok_or_else(|| {
+                            format!("Unknown swapchain composition: {}\n{}", value, USAGE)
+                        })?
+                }
+                "--size" => {
+                    let value = value()?;
+                    options.size = value
+                        .split_once('x')
+                        .and_then(|(width, height)| {
+                            Some((width.parse().ok()?, height.parse().ok()?))
+                        })
+                        .filter(|&(width, height)| width > 0 && height > 0)
+                        .ok_or_else(|| format!("Invalid size: {}\n{}", value, USAGE))?
+                }
+                "--rotation" => {
+                    let value = value()?;
+                    options.rotation = value
+                        .parse()
+                        .map_err(|_| format!("Invalid rotation: {}\n{}", value, USAGE))?
+                }
+                "--headless" => options.headless = true,
+                "--frames" => {
+                    let value = value()?;
+                    options.frames = value
+                        .parse()
+                        .map_err(|_| format!("Invalid frame count: {}\n{}", value, USAGE))?
+                }
+                "--output" => options.output = value()?.into(),
+                "--image-format" => {
+                    options.image_format = match value()?.as_str() {
+                        "png" => ImageFormat::Png,
+                        "ppm" => ImageFormat::Ppm,
+                        other => return Err(format!("Unknown image format: {}\n{}", other, USAGE)),
+                    }
+                }
+                "--depth-format" => {
+                    let value = value()?;
+                    options.depth.format = lookup(DEPTH_FORMATS, &value)
+                        .ok_or_else(|| format!("Unknown depth format: {}\n{}", value, USAGE))?
+                }
+                "--depth-compare" => {
+                    let value = value()?;
+                    options.depth.compare_op = lookup(DEPTH_COMPARE_OPS, &value)
+                        .ok_or_else(|| format!("Unknown depth compare op: {}\n{}", value, USAGE))?
+                }
+                _ => return Err(format!("Unknown argument: {}\n{}", arg, USAGE)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn lookup<T: Copy>(table: &[(&str, T)], name: &str) -> Option<T> {
+    table
+        .iter()
+        .find(|(table_name, _)| *table_name == name)
+        .map(|(_, value)| *value)
+}
@@ -0,0 +1,187 @@
+use sdl3_sys::gpu::{
+    SDL_GPUColorTargetBlendState, SDL_GPUColorTargetDescription, SDL_GPUCompareOp, SDL_GPUCullMode,
+    SDL_GPUDepthStencilState, SDL_GPUFillMode, SDL_GPUMultisampleState, SDL_GPUPrimitiveType,
+    SDL_GPURasterizerState, SDL_GPUSampleCount, SDL_GPUStencilOpState, SDL_GPUTextureFormat,
+    SDL_GPUVertexAttribute, SDL_GPUVertexBufferDescription, SDL_GPU_BLENDFACTOR_ONE,
+    SDL_GPU_BLENDFACTOR_ONE_MINUS_SRC_ALPHA, SDL_GPU_BLENDFACTOR_SRC_ALPHA,
+    SDL_GPU_BLENDFACTOR_ZERO, SDL_GPU_BLENDOP_ADD, SDL_GPU_COMPAREOP_INVALID,
+    SDL_GPU_CULLMODE_NONE, SDL_GPU_FILLMODE_FILL, SDL_GPU_FRONTFACE_COUNTER_CLOCKWISE,
+    SDL_GPU_PRIMITIVETYPE_TRIANGLELIST, SDL_GPU_STENCILOP_INVALID,
+};
+
+use crate::{
+    error::SdlResult,
+    sdl::{GpuDevice, GraphicsPipelineInfo, SdlGpuGraphicsPipeline, SdlGpuShader},
+    vertex::VertexLayout,
+};
+
+/// Writes the source color as-is.
+pub fn opaque_blend_state() -> SDL_GPUColorTargetBlendState {
+    SDL_GPUColorTargetBlendState {
+        src_color_blendfactor: SDL_GPU_BLENDFACTOR_ONE,
+        dst_color_blendfactor: SDL_GPU_BLENDFACTOR_ZERO,
+        color_blend_op: SDL_GPU_BLENDOP_ADD,
+        src_alpha_blendfactor: SDL_GPU_BLENDFACTOR_ONE,
+        dst_alpha_blendfactor: SDL_GPU_BLENDFACTOR_ZERO,
+        alpha_blend_op: SDL_GPU_BLENDOP_ADD,
+        color_write_mask: 0,
+        enable_blend: false,
+        enable_color_write_mask: false,
+        padding1: 0,
+        padding2: 0,
+    }
+}
+
+/// Standard non-premultiplied alpha blending.
+pub fn alpha_blend_state() -> SDL_GPUColorTargetBlendState {
+    SDL_GPUColorTargetBlendState {
+        src_color_blendfactor: SDL_GPU_BLENDFACTOR_SRC_ALPHA,
+        dst_color_blendfactor: SDL_GPU_BLENDFACTOR_ONE_MINUS_SRC_ALPHA,
+        color_blend_op: SDL_GPU_BLENDOP_ADD,
+        src_alpha_blendfactor: SDL_GPU_BLENDFACTOR_ONE,
+        dst_alpha_blendfactor: SDL_GPU_BLENDFACTOR_ONE,
+        alpha_blend_op: SDL_GPU_BLENDOP_ADD,
+        color_write_mask: 0,
+        enable_blend: true,
+        enable_color_write_mask: false,
+        padding1: 0,
+        padding2: 0,
+    }
+}
+
+fn unused_stencil_op_state() -> SDL_GPUStencilOpState {
+    SDL_GPUStencilOpState {
+        fail_op: SDL_GPU_STENCILOP_INVALID,
+        pass_op: SDL_GPU_STENCILOP_INVALID,
+        depth_fail_op: SDL_GPU_STENCILOP_INVALID,
+        compare_op: SDL_GPU_COMPAREOP_INVALID,
+    }
+}
+
+/// Describes a graphics pipeline, starting from defaults of a filled,
+/// unculled triangle list with no depth testing and no multisampling.
+pub struct GraphicsPipelineBuilder<'a> {
+    vertex_shader: &'a SdlGpuShader<'a>,
+    fragment_shader: &'a SdlGpuShader<'a>,
+    vertex_buffer_descriptions: Vec<SDL_GPUVertexBufferDescription>,
+    vertex_attributes: Vec<SDL_GPUVertexAttribute>,
+    primitive_type: SDL_GPUPrimitiveType,
+    rasterizer_state: SDL_GPURasterizerState,
+    multisample_state: SDL_GPUMultisampleState,
+    depth_stencil_state: SDL_GPUDepthStencilState,
+    color_target_descriptions: Vec<SDL_GPUColorTargetDescription>,
+    depth_stencil_format: Option<SDL_GPUTextureFormat>,
+}
+
+impl<'a> GraphicsPipelineBuilder<'a> {
+    pub fn new(vertex_shader: &'a SdlGpuShader<'a>, fragment_shader: &'a SdlGpuShader<'a>) -> Self {
+        Self {
+            vertex_shader,
+            fragment_shader,
+            vertex_buffer_descriptions: Vec::new(),
+            vertex_attributes: Vec::new(),
+            primitive_type: SDL_GPU_PRIMITIVETYPE_TRIANGLELIST,
+            rasterizer_state: SDL_GPURasterizerState {
+                fill_mode: SDL_GPU_FILLMODE_FILL,
+                cull_mode: SDL_GPU_CULLMODE_NONE,
+                front_face: SDL_GPU_FRONTFACE_COUNTER_CLOCKWISE,
+                depth_bias_constant_factor: 0.0,
+                depth_bias_clamp: 0.0,
+                depth_bias_slope_factor: 0.0,
+                enable_depth_bias: false,
+                enable_depth_clip: false,
+                padding1: 0,
+                padding2: 0,
+            },
+            multisample_state: SDL_GPUMultisampleState {
+                sample_count: SDL_GPUSampleCount::_1,
+                sample_mask: 0,
+                enable_mask: false,
+                padding1: 0,
+                padding2: 0,
+                padding3: 0,
+            },
+            depth_stencil_state: SDL_GPUDepthStencilState {
+                compare_op: SDL_GPU_COMPAREOP_INVALID,
+                back_stencil_state: unused_stencil_op_state(),
+                front_stencil_state: unused_stencil_op_state(),
+                compare_mask: 0,
+                write_mask: 0,
+                enable_depth_test: false,
+                enable_depth_write: false,
+                enable_stencil_test: false,
+                padding1: 0,
+                padding2: 0,
+                padding3: 0,
+            },
+            color_target_descriptions: Vec::new(),
+            depth_stencil_format: None,
+        }
+    }
+
+    /// Reads vertices of type `V` from the buffer bound to slot 0.
+    pub fn vertex_layout<V: VertexLayout>(mut self) -> Self {
+        self.vertex_buffer_descriptions = vec![V::buffer_description(0)];
+        self.vertex_attributes = V::attributes(0);
+        self
+    }
+
+    pub fn fill_mode(mut self, fill_mode: SDL_GPUFillMode) -> Self {
+        self.rasterizer_state.fill_mode = fill_mode;
+        self
+    }
+
+    pub fn cull_mode(mut self, cull_mode: SDL_GPUCullMode) -> Self {
+        self.rasterizer_state.cull_mode = cull_mode;
+        self
+    }
+
+    pub fn sample_count(mut self, sample_count: SDL_GPUSampleCount) -> Self {
+        self.multisample_state.sample_count = sample_count;
+        self
+    }
+
+    /// Adds a color target that isn't blended.
+    pub fn color_target(mut self, format: SDL_GPUTextureFormat) -> Self {
+        self.color_target_descriptions
+            .push(SDL_GPUColorTargetDescription {
+                format,
+                blend_state: opaque_blend_state(),
+            });
+        self
+    }
+
+    /// Sets the blend state of the most recently added color target.
+    pub fn blend(mut self, blend_state: SDL_GPUColorTargetBlendState) -> Self {
+        self.color_target_descriptions
+            .last_mut()
+            .expect("blend state set before adding a color target")
+            .blend_state = blend_state;
+        self
+    }
+
+    /// Enables depth testing and writing against a depth target of the given
+    /// format.
+    pub fn depth(mut self, format: SDL_GPUTextureFormat, compare_op: SDL_GPUCompareOp) -> Self {
+        self.depth_stencil_format = Some(format);
+        self.depth_stencil_state.compare_op = compare_op;
+        self.depth_stencil_state.enable_depth_test = true;
+        self.depth_stencil_state.enable_depth_write = true;
+        self
+    }
+
+    pub fn build<'d>(&self, device: &'d GpuDevice) -> SdlResult<SdlGpuGraphicsPipeline<'d>> {
+        device.create_graphics_pipeline(&GraphicsPipelineInfo {
+            vertex_shader: self.vertex_shader,
+            fragment_shader: self.fragment_shader,
+            vertex_buffer_descriptions: &self.vertex_buffer_descriptions,
+            vertex_attributes: &self.vertex_attributes,
+            primitive_type: self.primitive_type,
+            rasterizer_state: self.rasterizer_state,
+            multisample_state: self.multisample_state,
+            depth_stencil_state: self.depth_stencil_state,
+            color_target_descriptions: &self.color_target_descriptions,
+            depth_stencil_format: self.depth_stencil_format,
+        })
+    }
+}
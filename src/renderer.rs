@@ -0,0 +1,305 @@
+use glam::Mat4;
+use sdl3_sys::{
+    gpu::{
+        SDL_GPUBufferUsageFlags, SDL_GPUSampleCount, SDL_GPUTextureFormat, SDL_GPUViewport,
+        SDL_GPU_BUFFERUSAGE_INDEX, SDL_GPU_BUFFERUSAGE_VERTEX, SDL_GPU_CULLMODE_BACK,
+        SDL_GPU_FILLMODE_LINE, SDL_GPU_INDEXELEMENTSIZE_32BIT,
+    },
+    pixels::SDL_FColor,
+};
+
+use crate::{
+    error::SdlResult,
+    frames::FrameScheduler,
+    options::{name_of, DEPTH_FORMATS},
+    pipeline::{alpha_blend_state, GraphicsPipelineBuilder},
+    sdl::{
+        CommandBuffer, Fence, GpuDevice, RenderTexture, SdlGpuBuffer, SdlGpuGraphicsPipeline,
+        SdlGpuShader,
+    },
+    shaders,
+    streaming::StreamingUploader,
+    targets::{supported_sample_count, DepthSettings, RenderTargets},
+    Vertex,
+};
+
+const FRAMES_IN_FLIGHT: usize = 2;
+const STREAMING_BUFFER_SIZE: u32 = 64 * 1024;
+const CLEAR_COLOR: SDL_FColor = SDL_FColor {
+    r: 0.1,
+    g: 0.2,
+    b: 0.3,
+    a: 1.0,
+};
+
+/// Resources that are used by a single frame in flight. The buffers are
+/// created by the first frame to draw in the slot, and replaced whenever the
+/// mesh outgrows them.
+#[derive(Default)]
+struct FrameResources<'a> {
+    vertex_buffer: Option<SdlGpuBuffer<'a>>,
+    index_buffer: Option<SdlGpuBuffer<'a>>,
+}
+
+/// Returns `buffer`, after replacing it if it can't hold `size` bytes.
+fn reserve<'b, 'a>(
+    device: &'a GpuDevice,
+    buffer: &'b mut Option<SdlGpuBuffer<'a>>,
+    usage: SDL_GPUBufferUsageFlags,
+    size: u32,
+) -> SdlResult<&'b SdlGpuBuffer<'a>> {
+    if buffer.as_ref().is_none_or(|buffer| buffer.size() < size) {
+        // Free the old buffer before creating its replacement
+        *buffer = None;
+        *buffer = Some(device.create_buffer(usage, size)?);
+    }
+    Ok(buffer.as_ref().unwrap())
+}
+
+/// Draws a mesh into a color texture of a given format, which can be the
+/// window's swapchain or an offscreen texture.
+pub struct Renderer<'a> {
+    device: &'a GpuDevice,
+    // These three are only needed to rebuild the pipelines, which only debug
+    // builds with hot-reload do
+    #[cfg_attr(not(all(debug_assertions, feature = "hot-reload")), allow(dead_code))]
+    color_format: SDL_GPUTextureFormat,
+    #[cfg_attr(not(all(debug_assertions, feature = "hot-reload")), allow(dead_code))]
+    depth_settings: DepthSettings,
+    #[cfg_attr(not(all(debug_assertions, feature = "hot-reload")), allow(dead_code))]
+    sample_count: SDL_GPUSampleCount,
+    vertex_data: Vec<Vertex>,
+    index_data: Vec<u32>,
+    frames: FrameScheduler<'a, FrameResources<'a>>,
+    streaming_uploader: StreamingUploader<'a>,
+    render_targets: RenderTargets<'a>,
+    target_size: (u32, u32),
+    projection: Mat4,
+    pipeline: SdlGpuGraphicsPipeline<'a>,
+    wireframe_pipeline: SdlGpuGraphicsPipeline<'a>,
+    pub wireframe: bool,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(
+        device: &'a GpuDevice,
+        color_format: SDL_GPUTextureFormat,
+        depth_settings: DepthSettings,
+        sample_count: SDL_GPUSampleCount,
+    ) -> SdlResult<Self> {
+        // Load our shaders
+        let device_formats = device.shader_formats();
+        let vertex_shader = device.create_shader(
+            &shaders::shader::VS_MAIN
+                .shader_info(device_formats)
+                .expect("device was created for one of the shader's formats"),
+        )?;
+        let fragment_shader = device.create_shader(
+            &shaders::shader::FS_MAIN
+                .shader_info(device_formats)
+                .expect("device was created for one of the shader's formats"),
+        )?;
+
+        // Create the per-frame resources, including vertex and index buffers
+        // for each frame in flight that the mesh is streamed to every frame
+        let frames = FrameScheduler::new(FRAMES_IN_FLIGHT, |_| Ok(FrameResources::default()))?;
+        let streaming_uploader = StreamingUploader::new(device, STREAMING_BUFFER_SIZE)?;
+
+        // Pick our depth buffer format and sample count, and create the
+        // render targets themselves once we know the size of the target
+        let supported_depth_settings = depth_settings.supported_by(device);
+        if supported_depth_settings.format != depth_settings.format {
+            println!(
+                "Requested depth format isn't supported, falling back to {}",
+                name_of(DEPTH_FORMATS, supported_depth_settings.format)
+            );
+        }
+        let depth_settings = supported_depth_settings;
+        let supported_sample_count =
+            supported_sample_count(device, &[color_format, depth_settings.format], sample_count);
+        if supported_sample_count != sample_count {
+            println!("Requested sample count isn't supported, falling back to a lower one");
+        }
+        let sample_count = supported_sample_count;
+        let render_targets =
+            RenderTargets::new(device, color_format, depth_settings.format, sample_count);
+
+        // Create our pipelines, one filled and one for drawing in wireframe
+        let (pipeline, wireframe_pipeline) = create_pipelines(
+            device,
+            &vertex_shader,
+            &fragment_shader,
+            color_format,
+            depth_settings,
+            sample_count,
+        )?;
+
+        Ok(Self {
+            device,
+            color_format,
+            depth_settings,
+            sample_count,
+            vertex_data: Vec::new(),
+            index_data: Vec::new(),
+            frames,
+            streaming_uploader,
+            render_targets,
+            target_size: (0, 0),
+            projection: Mat4::IDENTITY,
+            pipeline,
+            wireframe_pipeline,
+            wireframe: false,
+        })
+    }
+
+    /// Replaces what's drawn, starting with the next frame.
+    pub fn set_mesh(&mut self, vertices: &[Vertex], indices: &[u32]) {
+        self.vertex_data.clear();
+        self.vertex_data.extend_from_slice(vertices);
+        self.index_data.clear();
+        self.index_data.extend_from_slice(indices);
+    }
+
+    /// Rebuilds the pipelines with new shaders.
+    #[cfg(all(debug_assertions, feature = "hot-reload"))]
+    pub fn reload_shaders(
+        &mut self,
+        vertex_shader: &SdlGpuShader,
+        fragment_shader: &SdlGpuShader,
+    ) -> SdlResult<()> {
+        (self.pipeline, self.wireframe_pipeline) = create_pipelines(
+            self.device,
+            vertex_shader,
+            fragment_shader,
+            self.color_format,
+            self.depth_settings,
+            self.sample_count,
+        )?;
+        Ok(())
+    }
+
+    /// Records drawing the mesh into `target`, after waiting for the GPU to
+    /// be done with the resources of the frame that last used this frame's
+    /// slot. Once the command buffer is submitted, its fence goes to
+    /// `end_frame`.
+    pub fn draw(
+        &mut self,
+        command_buffer: &mut CommandBuffer<'a>,
+        target: RenderTexture,
+        view: Mat4,
+        local_transform: Mat4,
+    ) -> SdlResult<()> {
+        let frame = self.frames.begin_frame()?;
+        let (width, height) = (target.width(), target.height());
+
+        // Recreate everything that depends on the target's size, but only
+        // when it changes
+        if (width, height) != self.target_size {
+            self.target_size = (width, height);
+            self.render_targets.resize(width, height)?;
+            self.projection = compute_projection(width, height);
+        }
+
+        // Upload this frame's mesh, so anything that animates or regenerates
+        // it only has to call set_mesh. The GPU is done with this slot's
+        // buffers, so they can be overwritten, or replaced if they're too
+        // small.
+        let buffers = if self.index_data.is_empty() {
+            None
+        } else {
+            let vertex_buffer = reserve(
+                self.device,
+                &mut frame.vertex_buffer,
+                SDL_GPU_BUFFERUSAGE_VERTEX,
+                std::mem::size_of_val(self.vertex_data.as_slice()) as u32,
+            )?;
+            let index_buffer = reserve(
+                self.device,
+                &mut frame.index_buffer,
+                SDL_GPU_BUFFERUSAGE_INDEX,
+                std::mem::size_of_val(self.index_data.as_slice()) as u32,
+            )?;
+            let mut copy_pass = command_buffer.begin_copy_pass()?;
+            self.streaming_uploader
+                .write(&mut copy_pass, &self.vertex_data, vertex_buffer)?;
+            self.streaming_uploader
+                .write(&mut copy_pass, &self.index_data, index_buffer)?;
+            Some((vertex_buffer, index_buffer))
+        };
+
+        // Draw
+        let color_target = self.render_targets.color_target(target, CLEAR_COLOR);
+        let depth_target = self.render_targets.depth_target();
+        let mut render_pass =
+            command_buffer.begin_render_pass(&[color_target], Some(&depth_target))?;
+
+        render_pass.bind_graphics_pipeline(if self.wireframe {
+            &self.wireframe_pipeline
+        } else {
+            &self.pipeline
+        });
+        let viewport = SDL_GPUViewport {
+            x: 0.0,
+            y: 0.0,
+            w: width as f32,
+            h: height as f32,
+            min_depth: 0.0,
+            max_depth: 1.0,
+        };
+        render_pass.set_viewport(&viewport);
+        // With nothing to draw, the pass still clears the target
+        if let Some((vertex_buffer, index_buffer)) = buffers {
+            render_pass.bind_vertex_buffers(0, &[(vertex_buffer, 0)]);
+            render_pass.bind_index_buffer(index_buffer, 0, SDL_GPU_INDEXELEMENTSIZE_32BIT);
+            let world_transform = self.projection * view;
+            render_pass.push_vertex_uniform_data(0, &world_transform);
+            render_pass.push_vertex_uniform_data(1, &local_transform);
+
+            render_pass.draw_indexed_primitives(self.index_data.len() as u32, 1, 0, 0, 0);
+        }
+        Ok(())
+    }
+
+    /// Records the fence of the command buffer `draw` was recorded into, so
+    /// the frame's resources are only reused once the GPU is done with them.
+    pub fn end_frame(&mut self, fence: Fence<'a>) {
+        self.frames.end_frame(fence);
+    }
+}
+
+fn create_pipelines<'d>(
+    device: &'d GpuDevice,
+    vertex_shader: &SdlGpuShader,
+    fragment_shader: &SdlGpuShader,
+    color_format: SDL_GPUTextureFormat,
+    depth_settings: DepthSettings,
+    sample_count: SDL_GPUSampleCount,
+) -> SdlResult<(SdlGpuGraphicsPipeline<'d>, SdlGpuGraphicsPipeline<'d>)> {
+    let builder = GraphicsPipelineBuilder::new(vertex_shader, fragment_shader)
+        .vertex_layout::<Vertex>()
+        .cull_mode(SDL_GPU_CULLMODE_BACK)
+        .sample_count(sample_count)
+        .color_target(color_format)
+        .blend(alpha_blend_state())
+        .depth(depth_settings.format, depth_settings.compare_op);
+    Ok((
+        builder.build(device)?,
+        builder.fill_mode(SDL_GPU_FILLMODE_LINE).build(device)?,
+    ))
+}
+
+fn compute_projection(width: u32, height: u32) -> Mat4 {
+    let projection = Mat4::perspective_rh(
+        45.0_f32.to_radians(),
+        width as f32 / height as f32,
+        1.0,
+        10000.0,
+    );
+    let correction = Mat4::from_cols_array_2d(&[
+        [-1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ]);
+    correction * projection
+}
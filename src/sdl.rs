@@ -1,16 +1,67 @@
+use std::{ffi::CStr, marker::PhantomData};
+
 use sdl3_sys::{
+    events::{
+        SDL_Event, SDL_EventType, SDL_PollEvent, SDL_WaitEvent, SDL_EVENT_GAMEPAD_ADDED,
+        SDL_EVENT_GAMEPAD_AXIS_MOTION, SDL_EVENT_GAMEPAD_BUTTON_DOWN, SDL_EVENT_GAMEPAD_BUTTON_UP,
+        SDL_EVENT_GAMEPAD_REMOVED, SDL_EVENT_KEY_DOWN, SDL_EVENT_KEY_UP,
+        SDL_EVENT_MOUSE_BUTTON_DOWN, SDL_EVENT_MOUSE_BUTTON_UP, SDL_EVENT_MOUSE_MOTION,
+        SDL_EVENT_MOUSE_WHEEL, SDL_EVENT_QUIT, SDL_EVENT_WINDOW_MINIMIZED,
+        SDL_EVENT_WINDOW_PIXEL_SIZE_CHANGED, SDL_EVENT_WINDOW_RESTORED,
+    },
+    gamepad::{
+        SDL_CloseGamepad, SDL_Gamepad, SDL_GamepadAxis, SDL_GamepadButton, SDL_GetGamepadName,
+        SDL_OpenGamepad,
+    },
     gpu::{
-        SDL_DestroyGPUDevice, SDL_GPUBuffer, SDL_GPUDevice, SDL_GPUGraphicsPipeline, SDL_GPUShader,
-        SDL_GPUTransferBuffer, SDL_ReleaseGPUBuffer, SDL_ReleaseGPUGraphicsPipeline,
-        SDL_ReleaseGPUShader, SDL_ReleaseGPUTransferBuffer,
+        SDL_AcquireGPUCommandBuffer, SDL_AcquireGPUSwapchainTexture, SDL_BeginGPUCopyPass,
+        SDL_BeginGPURenderPass, SDL_BindGPUGraphicsPipeline, SDL_BindGPUIndexBuffer,
+        SDL_BindGPUVertexBuffers, SDL_CancelGPUCommandBuffer, SDL_ClaimWindowForGPUDevice,
+        SDL_CreateGPUBuffer, SDL_CreateGPUDevice, SDL_CreateGPUGraphicsPipeline,
+        SDL_CreateGPUShader, SDL_CreateGPUTexture, SDL_CreateGPUTransferBuffer,
+        SDL_DestroyGPUDevice, SDL_DownloadFromGPUTexture, SDL_DrawGPUIndexedPrimitives,
+        SDL_EndGPUCopyPass, SDL_EndGPURenderPass, SDL_GPUBuffer, SDL_GPUBufferBinding,
+        SDL_GPUBufferCreateInfo, SDL_GPUBufferRegion, SDL_GPUBufferUsageFlags,
+        SDL_GPUColorTargetDescription, SDL_GPUColorTargetInfo, SDL_GPUCommandBuffer,
+        SDL_GPUCopyPass, SDL_GPUDepthStencilState, SDL_GPUDepthStencilTargetInfo, SDL_GPUDevice,
+        SDL_GPUFence, SDL_GPUGraphicsPipeline, SDL_GPUGraphicsPipelineCreateInfo,
+        SDL_GPUGraphicsPipelineTargetInfo, SDL_GPUIndexElementSize, SDL_GPULoadOp,
+        SDL_GPUMultisampleState, SDL_GPUPresentMode, SDL_GPUPrimitiveType, SDL_GPURasterizerState,
+        SDL_GPURenderPass, SDL_GPUSampleCount, SDL_GPUShader, SDL_GPUShaderCreateInfo,
+        SDL_GPUShaderFormat, SDL_GPUShaderStage, SDL_GPUStoreOp, SDL_GPUSwapchainComposition,
+        SDL_GPUTexture, SDL_GPUTextureCreateInfo, SDL_GPUTextureFormat, SDL_GPUTextureRegion,
+        SDL_GPUTextureSupportsFormat, SDL_GPUTextureSupportsSampleCount,
+        SDL_GPUTextureTransferInfo, SDL_GPUTextureType, SDL_GPUTextureUsageFlags,
+        SDL_GPUTransferBuffer, SDL_GPUTransferBufferCreateInfo, SDL_GPUTransferBufferLocation,
+        SDL_GPUTransferBufferUsage, SDL_GPUVertexAttribute, SDL_GPUVertexBufferDescription,
+        SDL_GPUVertexInputState, SDL_GPUViewport, SDL_GetGPUDeviceDriver, SDL_GetGPUShaderFormats,
+        SDL_GetGPUSwapchainTextureFormat, SDL_MapGPUTransferBuffer, SDL_PushGPUVertexUniformData,
+        SDL_ReleaseGPUBuffer, SDL_ReleaseGPUFence, SDL_ReleaseGPUGraphicsPipeline,
+        SDL_ReleaseGPUShader, SDL_ReleaseGPUTexture, SDL_ReleaseGPUTransferBuffer,
+        SDL_ReleaseWindowFromGPUDevice, SDL_SetGPUSwapchainParameters, SDL_SetGPUViewport,
+        SDL_SubmitGPUCommandBuffer, SDL_SubmitGPUCommandBufferAndAcquireFence,
+        SDL_UnmapGPUTransferBuffer, SDL_UploadToGPUBuffer, SDL_WaitForGPUFences,
+        SDL_WindowSupportsGPUPresentMode, SDL_WindowSupportsGPUSwapchainComposition,
+        SDL_GPU_TEXTUREFORMAT_INVALID,
     },
-    video::{SDL_DestroyWindow, SDL_Window},
+    hints::SDL_SetHint,
+    init::{SDL_Init, SDL_InitFlags, SDL_Quit},
+    joystick::SDL_JoystickID,
+    keycode::SDL_Keycode,
+    mouse::{SDL_SetWindowRelativeMouseMode, SDL_MOUSEWHEEL_FLIPPED},
+    pixels::SDL_FColor,
+    video::{SDL_CreateWindow, SDL_DestroyWindow, SDL_Window, SDL_WindowFlags},
+};
+
+use crate::{
+    error::{SdlFunctionResult, SdlResult},
+    util::{as_bytes, null_terminated_sdl_str, Pod},
 };
 
 macro_rules! destroy_wrapper {
     ($new_ty:ident, $sdl_ty:ident, $sdl_destroy:ident) => {
         #[repr(transparent)]
-        pub struct $new_ty(pub *mut $sdl_ty);
+        pub struct $new_ty(*mut $sdl_ty);
 
         impl Drop for $new_ty {
             fn drop(&mut self) {
@@ -20,37 +71,451 @@ macro_rules! destroy_wrapper {
             }
         }
 
-        impl From<*mut $sdl_ty> for $new_ty {
-            fn from(value: *mut $sdl_ty) -> Self {
-                $new_ty(value)
+        impl $new_ty {
+            /// # Safety
+            ///
+            /// `value` must be valid and must not be owned by anything else.
+            pub unsafe fn from_raw(value: *mut $sdl_ty) -> Self {
+                Self(value)
             }
         }
     };
 }
 
 destroy_wrapper!(SdlWindow, SDL_Window, SDL_DestroyWindow);
-destroy_wrapper!(SdlGpuDevice, SDL_GPUDevice, SDL_DestroyGPUDevice);
+
+impl SdlWindow {
+    pub fn new(title: &CStr, width: i32, height: i32, flags: SDL_WindowFlags) -> SdlResult<Self> {
+        unsafe {
+            let window = SDL_CreateWindow(title.as_ptr(), width, height, flags).ok()?;
+            Ok(Self::from_raw(window))
+        }
+    }
+
+    /// Hides the cursor and keeps reporting relative mouse motion, even
+    /// once the cursor would have left the window.
+    pub fn set_relative_mouse_mode(&self, enabled: bool) -> SdlResult<()> {
+        unsafe { SDL_SetWindowRelativeMouseMode(self.0, enabled).ok() }
+    }
+}
+
+destroy_wrapper!(SdlGamepad, SDL_Gamepad, SDL_CloseGamepad);
+
+impl SdlGamepad {
+    pub fn open(id: SDL_JoystickID) -> SdlResult<Self> {
+        unsafe {
+            let gamepad = SDL_OpenGamepad(id).ok()?;
+            Ok(Self::from_raw(gamepad))
+        }
+    }
+
+    pub fn name(&self) -> SdlResult<&str> {
+        let name = unsafe { SDL_GetGamepadName(self.0) };
+        Ok(null_terminated_sdl_str(name)?.unwrap_or("Unnamed gamepad"))
+    }
+}
+
+/// Sets a hint unless it was already set in the environment, which takes
+/// precedence.
+pub fn set_hint(name: &CStr, value: &CStr) {
+    unsafe {
+        SDL_SetHint(name.as_ptr(), value.as_ptr());
+    }
+}
+
+/// Initializes SDL on creation and shuts it down when dropped. Everything
+/// else created through this module must be dropped before this is.
+pub struct SdlContext(());
+
+impl SdlContext {
+    pub fn init(flags: SDL_InitFlags) -> SdlResult<Self> {
+        unsafe {
+            SDL_Init(flags).ok()?;
+        }
+        Ok(Self(()))
+    }
+}
+
+impl Drop for SdlContext {
+    fn drop(&mut self) {
+        unsafe {
+            SDL_Quit();
+        }
+    }
+}
+
+pub enum Event {
+    Quit,
+    /// A key was pressed. Doesn't include repeats from holding it down.
+    KeyDown(SDL_Keycode),
+    KeyUp(SDL_Keycode),
+    /// Relative movement of the mouse, in pixels.
+    MouseMotion {
+        xrel: f32,
+        yrel: f32,
+    },
+    /// A mouse button, such as `SDL_BUTTON_LEFT`, was pressed.
+    MouseButtonDown(u8),
+    MouseButtonUp(u8),
+    /// Vertical scrolling, positive away from the user regardless of the
+    /// platform's "natural" scrolling setting.
+    MouseWheel(f32),
+    GamepadButtonDown(SDL_GamepadButton),
+    GamepadButtonUp(SDL_GamepadButton),
+    /// A stick or trigger moved. Sticks go from -32768 to 32767, with +Y
+    /// being down, and triggers from 0 to 32767.
+    GamepadAxis {
+        axis: SDL_GamepadAxis,
+        value: i16,
+    },
+    /// A gamepad was plugged in, or was already plugged in when SDL started.
+    GamepadAdded(SDL_JoystickID),
+    GamepadRemoved(SDL_JoystickID),
+    /// The window's size in pixels changed, which may be to nothing.
+    WindowResized {
+        width: i32,
+        height: i32,
+    },
+    WindowMinimized,
+    WindowRestored,
+    Other,
+}
+
+impl From<&SDL_Event> for Event {
+    fn from(event: &SDL_Event) -> Self {
+        // Every variant of the union starts with the event type, so reading
+        // it is always valid. The type then tells us which variant is live.
+        unsafe {
+            match SDL_EventType(event.r#type) {
+                SDL_EVENT_QUIT => Event::Quit,
+                SDL_EVENT_KEY_DOWN if !event.key.repeat => Event::KeyDown(event.key.key),
+                SDL_EVENT_KEY_UP => Event::KeyUp(event.key.key),
+                SDL_EVENT_MOUSE_MOTION => Event::MouseMotion {
+                    xrel: event.motion.xrel,
+                    yrel: event.motion.yrel,
+                },
+                SDL_EVENT_MOUSE_BUTTON_DOWN => Event::MouseButtonDown(event.button.button),
+                SDL_EVENT_MOUSE_BUTTON_UP => Event::MouseButtonUp(event.button.button),
+                SDL_EVENT_MOUSE_WHEEL => {
+                    if event.wheel.direction == SDL_MOUSEWHEEL_FLIPPED {
+                        Event::MouseWheel(-event.wheel.y)
+                    } else {
+                        Event::MouseWheel(event.wheel.y)
+                    }
+                }
+                SDL_EVENT_GAMEPAD_BUTTON_DOWN => {
+                    Event::GamepadButtonDown(SDL_GamepadButton(event.gbutton.button.into()))
+                }
+                SDL_EVENT_GAMEPAD_BUTTON_UP => {
+                    Event::GamepadButtonUp(SDL_GamepadButton(event.gbutton.button.into()))
+                }
+                SDL_EVENT_GAMEPAD_AXIS_MOTION => Event::GamepadAxis {
+                    axis: SDL_GamepadAxis(event.gaxis.axis.into()),
+                    value: event.gaxis.value,
+                },
+                SDL_EVENT_GAMEPAD_ADDED => Event::GamepadAdded(event.gdevice.which),
+                SDL_EVENT_GAMEPAD_REMOVED => Event::GamepadRemoved(event.gdevice.which),
+                SDL_EVENT_WINDOW_PIXEL_SIZE_CHANGED => Event::WindowResized {
+                    width: event.window.data1,
+                    height: event.window.data2,
+                },
+                SDL_EVENT_WINDOW_MINIMIZED => Event::WindowMinimized,
+                SDL_EVENT_WINDOW_RESTORED => Event::WindowRestored,
+                _ => Event::Other,
+            }
+        }
+    }
+}
+
+pub fn poll_event() -> Option<Event> {
+    let mut event = SDL_Event {
+        padding: [0u8; 128],
+    };
+    if unsafe { SDL_PollEvent(&mut event) } {
+        Some(Event::from(&event))
+    } else {
+        None
+    }
+}
+
+/// Blocks until there's an event. Returns None if waiting failed.
+pub fn wait_event() -> Option<Event> {
+    let mut event = SDL_Event {
+        padding: [0u8; 128],
+    };
+    if unsafe { SDL_WaitEvent(&mut event) } {
+        Some(Event::from(&event))
+    } else {
+        None
+    }
+}
+
+/// Owns an `SDL_GPUDevice`. Resources created through it borrow the device,
+/// so they can't outlive it.
+pub struct GpuDevice(*mut SDL_GPUDevice);
+
+impl Drop for GpuDevice {
+    fn drop(&mut self) {
+        unsafe {
+            SDL_DestroyGPUDevice(self.0);
+        }
+    }
+}
+
+pub struct ShaderInfo<'a> {
+    pub code: &'a [u8],
+    pub entrypoint: &'a CStr,
+    pub format: SDL_GPUShaderFormat,
+    pub stage: SDL_GPUShaderStage,
+    pub num_samplers: u32,
+    pub num_storage_textures: u32,
+    pub num_storage_buffers: u32,
+    pub num_uniform_buffers: u32,
+}
+
+pub struct GraphicsPipelineInfo<'a> {
+    pub vertex_shader: &'a SdlGpuShader<'a>,
+    pub fragment_shader: &'a SdlGpuShader<'a>,
+    pub vertex_buffer_descriptions: &'a [SDL_GPUVertexBufferDescription],
+    pub vertex_attributes: &'a [SDL_GPUVertexAttribute],
+    pub primitive_type: SDL_GPUPrimitiveType,
+    pub rasterizer_state: SDL_GPURasterizerState,
+    pub multisample_state: SDL_GPUMultisampleState,
+    pub depth_stencil_state: SDL_GPUDepthStencilState,
+    pub color_target_descriptions: &'a [SDL_GPUColorTargetDescription],
+    pub depth_stencil_format: Option<SDL_GPUTextureFormat>,
+}
+
+impl GpuDevice {
+    /// Creates a device for the first driver that supports any of the given
+    /// shader formats, or for the named driver if there is one.
+    pub fn new(
+        format_flags: SDL_GPUShaderFormat,
+        debug_mode: bool,
+        driver: Option<&CStr>,
+    ) -> SdlResult<Self> {
+        let name = driver.map_or(std::ptr::null(), |driver| driver.as_ptr());
+        let device = unsafe { SDL_CreateGPUDevice(format_flags, debug_mode, name).ok()? };
+        Ok(Self(device))
+    }
+
+    pub fn shader_formats(&self) -> SDL_GPUShaderFormat {
+        unsafe { SDL_GetGPUShaderFormats(self.0) }
+    }
+
+    pub fn driver(&self) -> SdlResult<&str> {
+        let driver = unsafe { SDL_GetGPUDeviceDriver(self.0).ok()? };
+        Ok(null_terminated_sdl_str(driver)?.unwrap_or_default())
+    }
+
+    pub fn claim_window<'a>(&'a self, window: &'a SdlWindow) -> SdlResult<ClaimedWindow<'a>> {
+        unsafe {
+            SDL_ClaimWindowForGPUDevice(self.0, window.0).ok()?;
+        }
+        Ok(ClaimedWindow {
+            device: self,
+            window,
+        })
+    }
+
+    pub fn create_shader(&self, info: &ShaderInfo) -> SdlResult<SdlGpuShader<'_>> {
+        let desc = SDL_GPUShaderCreateInfo {
+            code_size: info.code.len(),
+            code: info.code.as_ptr(),
+            entrypoint: info.entrypoint.as_ptr(),
+            format: info.format,
+            stage: info.stage,
+            num_samplers: info.num_samplers,
+            num_storage_textures: info.num_storage_textures,
+            num_storage_buffers: info.num_storage_buffers,
+            num_uniform_buffers: info.num_uniform_buffers,
+            props: 0,
+        };
+        unsafe {
+            let shader = SDL_CreateGPUShader(self.0, &desc).ok()?;
+            Ok(SdlGpuShader::new(shader, self))
+        }
+    }
+
+    pub fn create_buffer(
+        &self,
+        usage: SDL_GPUBufferUsageFlags,
+        size: u32,
+    ) -> SdlResult<SdlGpuBuffer<'_>> {
+        let desc = SDL_GPUBufferCreateInfo {
+            usage,
+            size,
+            props: 0,
+        };
+        unsafe {
+            let buffer = SDL_CreateGPUBuffer(self.0, &desc).ok()?;
+            Ok(SdlGpuBuffer::new(buffer, self, size))
+        }
+    }
+
+    pub fn create_transfer_buffer(
+        &self,
+        usage: SDL_GPUTransferBufferUsage,
+        size: u32,
+    ) -> SdlResult<SdlGpuTransferBuffer<'_>> {
+        let desc = SDL_GPUTransferBufferCreateInfo {
+            usage,
+            size,
+            props: 0,
+        };
+        unsafe {
+            let buffer = SDL_CreateGPUTransferBuffer(self.0, &desc).ok()?;
+            Ok(SdlGpuTransferBuffer::new(buffer, self, size))
+        }
+    }
+
+    pub fn create_texture(&self, info: &SDL_GPUTextureCreateInfo) -> SdlResult<SdlGpuTexture<'_>> {
+        unsafe {
+            let texture = SDL_CreateGPUTexture(self.0, info).ok()?;
+            Ok(SdlGpuTexture::new(texture, self, info.width, info.height))
+        }
+    }
+
+    pub fn texture_supports_format(
+        &self,
+        format: SDL_GPUTextureFormat,
+        texture_type: SDL_GPUTextureType,
+        usage: SDL_GPUTextureUsageFlags,
+    ) -> bool {
+        unsafe { SDL_GPUTextureSupportsFormat(self.0, format, texture_type, usage) }
+    }
+
+    pub fn texture_supports_sample_count(
+        &self,
+        format: SDL_GPUTextureFormat,
+        sample_count: SDL_GPUSampleCount,
+    ) -> bool {
+        unsafe { SDL_GPUTextureSupportsSampleCount(self.0, format, sample_count) }
+    }
+
+    pub fn create_graphics_pipeline(
+        &self,
+        info: &GraphicsPipelineInfo,
+    ) -> SdlResult<SdlGpuGraphicsPipeline<'_>> {
+        assert!(std::ptr::eq(info.vertex_shader.device, self));
+        assert!(std::ptr::eq(info.fragment_shader.device, self));
+        let desc = SDL_GPUGraphicsPipelineCreateInfo {
+            vertex_shader: info.vertex_shader.get(),
+            fragment_shader: info.fragment_shader.get(),
+            vertex_input_state: SDL_GPUVertexInputState {
+                vertex_buffer_descriptions: info.vertex_buffer_descriptions.as_ptr(),
+                num_vertex_buffers: info.vertex_buffer_descriptions.len() as u32,
+                vertex_attributes: info.vertex_attributes.as_ptr(),
+                num_vertex_attributes: info.vertex_attributes.len() as u32,
+            },
+            primitive_type: info.primitive_type,
+            rasterizer_state: info.rasterizer_state,
+            multisample_state: info.multisample_state,
+            depth_stencil_state: info.depth_stencil_state,
+            target_info: SDL_GPUGraphicsPipelineTargetInfo {
+                color_target_descriptions: info.color_target_descriptions.as_ptr(),
+                num_color_targets: info.color_target_descriptions.len() as u32,
+                depth_stencil_format: info
+                    .depth_stencil_format
+                    .unwrap_or(SDL_GPU_TEXTUREFORMAT_INVALID),
+                has_depth_stencil_target: info.depth_stencil_format.is_some(),
+                padding1: 0,
+                padding2: 0,
+                padding3: 0,
+            },
+            props: 0,
+        };
+        unsafe {
+            let pipeline = SDL_CreateGPUGraphicsPipeline(self.0, &desc).ok()?;
+            Ok(SdlGpuGraphicsPipeline::new(pipeline, self))
+        }
+    }
+}
+
+/// A window whose swapchain is owned by a `GpuDevice`. The window is released
+/// from the device when this is dropped.
+pub struct ClaimedWindow<'a> {
+    device: &'a GpuDevice,
+    window: &'a SdlWindow,
+}
+
+impl<'a> ClaimedWindow<'a> {
+    pub fn get(&self) -> *mut SDL_Window {
+        self.window.0
+    }
+
+    pub fn window(&self) -> &'a SdlWindow {
+        self.window
+    }
+
+    pub fn supports_present_mode(&self, present_mode: SDL_GPUPresentMode) -> bool {
+        unsafe { SDL_WindowSupportsGPUPresentMode(self.device.0, self.window.0, present_mode) }
+    }
+
+    pub fn supports_swapchain_composition(&self, composition: SDL_GPUSwapchainComposition) -> bool {
+        unsafe {
+            SDL_WindowSupportsGPUSwapchainComposition(self.device.0, self.window.0, composition)
+        }
+    }
+
+    /// Both must be supported by the window, which SDL only guarantees for
+    /// VSYNC and SDR.
+    pub fn set_swapchain_parameters(
+        &self,
+        composition: SDL_GPUSwapchainComposition,
+        present_mode: SDL_GPUPresentMode,
+    ) -> SdlResult<()> {
+        unsafe {
+            SDL_SetGPUSwapchainParameters(self.device.0, self.window.0, composition, present_mode)
+                .ok()
+        }
+    }
+
+    /// The format of the swapchain's textures, which depends on the
+    /// swapchain composition.
+    pub fn swapchain_texture_format(&self) -> SDL_GPUTextureFormat {
+        unsafe { SDL_GetGPUSwapchainTextureFormat(self.device.0, self.window.0) }
+    }
+}
+
+impl Drop for ClaimedWindow<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            SDL_ReleaseWindowFromGPUDevice(self.device.0, self.window.0);
+        }
+    }
+}
 
 macro_rules! device_resource {
-    ($new_ty:ident, $sdl_ty:ident, $sdl_destroy:ident) => {
-        pub struct $new_ty {
+    ($new_ty:ident, $sdl_ty:ident, $sdl_destroy:ident $(, $field:ident: $field_ty:ty)*) => {
+        pub struct $new_ty<'a> {
             inner: *mut $sdl_ty,
-            device: *mut SDL_GPUDevice,
+            device: &'a GpuDevice,
+            $($field: $field_ty,)*
         }
 
-        impl Drop for $new_ty {
+        impl Drop for $new_ty<'_> {
             fn drop(&mut self) {
                 unsafe {
-                    $sdl_destroy(self.device, self.inner);
+                    $sdl_destroy(self.device.0, self.inner);
                 }
             }
         }
 
-        impl $new_ty {
-            pub fn new(value: *mut $sdl_ty, device: *mut SDL_GPUDevice) -> Self {
+        impl<'a> $new_ty<'a> {
+            /// # Safety
+            ///
+            /// `value` must have been created by `device` and must not be
+            /// owned by anything else.
+            pub unsafe fn new(
+                value: *mut $sdl_ty,
+                device: &'a GpuDevice
+                $(, $field: $field_ty)*
+            ) -> Self {
                 Self {
                     inner: value,
                     device,
+                    $($field,)*
                 }
             }
 
@@ -62,14 +527,523 @@ macro_rules! device_resource {
 }
 
 device_resource!(SdlGpuShader, SDL_GPUShader, SDL_ReleaseGPUShader);
-device_resource!(SdlGpuBuffer, SDL_GPUBuffer, SDL_ReleaseGPUBuffer);
+device_resource!(SdlGpuBuffer, SDL_GPUBuffer, SDL_ReleaseGPUBuffer, size: u32);
 device_resource!(
     SdlGpuTransferBuffer,
     SDL_GPUTransferBuffer,
-    SDL_ReleaseGPUTransferBuffer
+    SDL_ReleaseGPUTransferBuffer,
+    size: u32
+);
+device_resource!(
+    SdlGpuTexture,
+    SDL_GPUTexture,
+    SDL_ReleaseGPUTexture,
+    width: u32,
+    height: u32
 );
 device_resource!(
     SdlGpuGraphicsPipeline,
     SDL_GPUGraphicsPipeline,
     SDL_ReleaseGPUGraphicsPipeline
 );
+
+impl SdlGpuBuffer<'_> {
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+}
+
+impl SdlGpuTexture<'_> {
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+impl SdlGpuTransferBuffer<'_> {
+    pub fn map(&mut self, cycle: bool) -> SdlResult<TransferBufferMapping<'_>> {
+        let data = unsafe {
+            let ptr = SDL_MapGPUTransferBuffer(self.device.0, self.inner, cycle).ok()?;
+            std::slice::from_raw_parts_mut(ptr as *mut u8, self.size as usize)
+        };
+        Ok(TransferBufferMapping {
+            device: self.device,
+            buffer: self.inner,
+            data,
+        })
+    }
+}
+
+/// The mapped contents of a transfer buffer, unmapped when dropped.
+pub struct TransferBufferMapping<'a> {
+    device: &'a GpuDevice,
+    buffer: *mut SDL_GPUTransferBuffer,
+    data: &'a mut [u8],
+}
+
+impl std::ops::Deref for TransferBufferMapping<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        self.data
+    }
+}
+
+impl std::ops::DerefMut for TransferBufferMapping<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.data
+    }
+}
+
+impl Drop for TransferBufferMapping<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            SDL_UnmapGPUTransferBuffer(self.device.0, self.buffer);
+        }
+    }
+}
+
+/// A fence signalled when a submitted command buffer finishes, released when
+/// dropped.
+pub struct Fence<'a> {
+    inner: *mut SDL_GPUFence,
+    device: &'a GpuDevice,
+}
+
+impl Fence<'_> {
+    pub fn wait(&self) -> SdlResult<()> {
+        unsafe { SDL_WaitForGPUFences(self.device.0, true, &self.inner, 1).ok() }
+    }
+}
+
+impl Drop for Fence<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            SDL_ReleaseGPUFence(self.device.0, self.inner);
+        }
+    }
+}
+
+/// The swapchain texture acquired for a frame. It mutably borrows the
+/// command buffer that acquired it, which the rest of the frame is recorded
+/// into through this, so neither the texture nor its `RenderTexture` can
+/// outlive the command buffer being submitted.
+pub struct SwapchainTexture<'cb, 'a> {
+    command_buffer: &'cb mut CommandBuffer<'a>,
+    target: SwapchainTarget<'cb>,
+}
+
+impl<'cb> SwapchainTexture<'cb, '_> {
+    pub fn render_texture(&self) -> RenderTexture<'cb> {
+        RenderTexture::Swapchain(self.target)
+    }
+}
+
+impl<'a> std::ops::Deref for SwapchainTexture<'_, 'a> {
+    type Target = CommandBuffer<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        self.command_buffer
+    }
+}
+
+impl std::ops::DerefMut for SwapchainTexture<'_, '_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.command_buffer
+    }
+}
+
+/// A swapchain texture as a render target, only valid while the command
+/// buffer that acquired it is borrowed for `'cb`.
+#[derive(Copy, Clone)]
+pub struct SwapchainTarget<'cb> {
+    texture: *mut SDL_GPUTexture,
+    width: u32,
+    height: u32,
+    _command_buffer: PhantomData<&'cb ()>,
+}
+
+/// A texture that can be rendered to, either one we created or the
+/// swapchain's.
+#[derive(Copy, Clone)]
+pub enum RenderTexture<'t> {
+    Texture(&'t SdlGpuTexture<'t>),
+    Swapchain(SwapchainTarget<'t>),
+}
+
+impl RenderTexture<'_> {
+    fn get(self) -> *mut SDL_GPUTexture {
+        match self {
+            RenderTexture::Texture(texture) => texture.get(),
+            RenderTexture::Swapchain(target) => target.texture,
+        }
+    }
+
+    pub fn width(self) -> u32 {
+        match self {
+            RenderTexture::Texture(texture) => texture.width(),
+            RenderTexture::Swapchain(target) => target.width,
+        }
+    }
+
+    pub fn height(self) -> u32 {
+        match self {
+            RenderTexture::Texture(texture) => texture.height(),
+            RenderTexture::Swapchain(target) => target.height,
+        }
+    }
+}
+
+/// A color target of a render pass, like `SDL_GPUColorTargetInfo` but with
+/// borrowed textures.
+pub struct ColorTarget<'t> {
+    pub texture: RenderTexture<'t>,
+    pub clear_color: SDL_FColor,
+    pub load_op: SDL_GPULoadOp,
+    pub store_op: SDL_GPUStoreOp,
+    /// Where multisampled contents are resolved to, if `store_op` resolves.
+    pub resolve_texture: Option<RenderTexture<'t>>,
+    pub cycle: bool,
+    pub cycle_resolve_texture: bool,
+}
+
+impl ColorTarget<'_> {
+    fn info(&self) -> SDL_GPUColorTargetInfo {
+        SDL_GPUColorTargetInfo {
+            texture: self.texture.get(),
+            mip_level: 0,
+            layer_or_depth_plane: 0,
+            clear_color: self.clear_color,
+            load_op: self.load_op,
+            store_op: self.store_op,
+            resolve_texture: self
+                .resolve_texture
+                .map_or(std::ptr::null_mut(), RenderTexture::get),
+            resolve_mip_level: 0,
+            resolve_layer: 0,
+            cycle: self.cycle,
+            cycle_resolve_texture: self.cycle_resolve_texture,
+            padding1: 0,
+            padding2: 0,
+        }
+    }
+}
+
+/// The depth stencil target of a render pass, like
+/// `SDL_GPUDepthStencilTargetInfo` but with a borrowed texture.
+pub struct DepthStencilTarget<'t> {
+    pub texture: &'t SdlGpuTexture<'t>,
+    pub clear_depth: f32,
+    pub load_op: SDL_GPULoadOp,
+    pub store_op: SDL_GPUStoreOp,
+    pub stencil_load_op: SDL_GPULoadOp,
+    pub stencil_store_op: SDL_GPUStoreOp,
+    pub cycle: bool,
+    pub clear_stencil: u8,
+}
+
+impl DepthStencilTarget<'_> {
+    fn info(&self) -> SDL_GPUDepthStencilTargetInfo {
+        SDL_GPUDepthStencilTargetInfo {
+            texture: self.texture.get(),
+            clear_depth: self.clear_depth,
+            load_op: self.load_op,
+            store_op: self.store_op,
+            stencil_load_op: self.stencil_load_op,
+            stencil_store_op: self.stencil_store_op,
+            cycle: self.cycle,
+            clear_stencil: self.clear_stencil,
+            padding1: 0,
+            padding2: 0,
+        }
+    }
+}
+
+/// A command buffer being recorded. It must be finished with
+/// `submit_and_acquire_fence` or `cancel`; one that is dropped without either
+/// (e.g. on an early return) is cancelled, or submitted if a swapchain texture
+/// has already been acquired since SDL does not allow cancelling those.
+///
+/// Passes mutably borrow the command buffer, so only one can be open at a
+/// time and the command buffer can't be submitted until it has ended.
+pub struct CommandBuffer<'a> {
+    inner: *mut SDL_GPUCommandBuffer,
+    device: &'a GpuDevice,
+    acquired_swapchain: bool,
+}
+
+impl GpuDevice {
+    pub fn acquire_command_buffer(&self) -> SdlResult<CommandBuffer<'_>> {
+        let command_buffer = unsafe { SDL_AcquireGPUCommandBuffer(self.0).ok()? };
+        Ok(CommandBuffer {
+            inner: command_buffer,
+            device: self,
+            acquired_swapchain: false,
+        })
+    }
+}
+
+impl<'a> CommandBuffer<'a> {
+    /// Returns None if there's nothing to render to, such as while the
+    /// window is minimized, in which case the command buffer can still be
+    /// cancelled.
+    pub fn acquire_swapchain_texture(
+        &mut self,
+        window: &ClaimedWindow,
+    ) -> SdlResult<Option<SwapchainTexture<'_, 'a>>> {
+        let mut texture = std::ptr::null_mut();
+        let mut width = 0;
+        let mut height = 0;
+        unsafe {
+            SDL_AcquireGPUSwapchainTexture(
+                self.inner,
+                window.get(),
+                &mut texture,
+                &mut width,
+                &mut height,
+            )
+            .ok()?;
+        }
+        if texture.is_null() {
+            return Ok(None);
+        }
+        self.acquired_swapchain = true;
+        Ok(Some(SwapchainTexture {
+            command_buffer: self,
+            target: SwapchainTarget {
+                texture,
+                width,
+                height,
+                _command_buffer: PhantomData,
+            },
+        }))
+    }
+
+    pub fn push_vertex_uniform_data<T: Pod>(&mut self, slot: u32, data: &T) {
+        let bytes = as_bytes(data);
+        unsafe {
+            SDL_PushGPUVertexUniformData(
+                self.inner,
+                slot,
+                bytes.as_ptr() as *const _,
+                bytes.len() as u32,
+            );
+        }
+    }
+
+    pub fn begin_copy_pass(&mut self) -> SdlResult<CopyPass<'_, 'a>> {
+        let copy_pass = unsafe { SDL_BeginGPUCopyPass(self.inner).ok()? };
+        Ok(CopyPass {
+            inner: copy_pass,
+            _command_buffer: self,
+        })
+    }
+
+    pub fn begin_render_pass(
+        &mut self,
+        color_targets: &[ColorTarget],
+        depth_stencil_target: Option<&DepthStencilTarget>,
+    ) -> SdlResult<RenderPass<'_, 'a>> {
+        let color_targets: Vec<_> = color_targets.iter().map(ColorTarget::info).collect();
+        let depth_stencil_target = depth_stencil_target.map(DepthStencilTarget::info);
+        let render_pass = unsafe {
+            SDL_BeginGPURenderPass(
+                self.inner,
+                color_targets.as_ptr(),
+                color_targets.len() as u32,
+                depth_stencil_target
+                    .as_ref()
+                    .map_or(std::ptr::null(), |target| target as *const _),
+            )
+            .ok()?
+        };
+        Ok(RenderPass {
+            inner: render_pass,
+            command_buffer: self,
+        })
+    }
+
+    pub fn submit_and_acquire_fence(self) -> SdlResult<Fence<'a>> {
+        let command_buffer = std::mem::ManuallyDrop::new(self);
+        let fence =
+            unsafe { SDL_SubmitGPUCommandBufferAndAcquireFence(command_buffer.inner).ok()? };
+        Ok(Fence {
+            inner: fence,
+            device: command_buffer.device,
+        })
+    }
+
+    pub fn cancel(self) -> SdlResult<()> {
+        let command_buffer = std::mem::ManuallyDrop::new(self);
+        unsafe { SDL_CancelGPUCommandBuffer(command_buffer.inner).ok() }
+    }
+}
+
+impl Drop for CommandBuffer<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            if self.acquired_swapchain {
+                SDL_SubmitGPUCommandBuffer(self.inner);
+            } else {
+                SDL_CancelGPUCommandBuffer(self.inner);
+            }
+        }
+    }
+}
+
+/// A copy pass, ended when dropped.
+pub struct CopyPass<'c, 'a> {
+    inner: *mut SDL_GPUCopyPass,
+    _command_buffer: &'c mut CommandBuffer<'a>,
+}
+
+impl CopyPass<'_, '_> {
+    pub fn upload_to_buffer(
+        &mut self,
+        source: &SdlGpuTransferBuffer,
+        source_offset: u32,
+        destination: &SdlGpuBuffer,
+        destination_offset: u32,
+        size: u32,
+        cycle: bool,
+    ) {
+        let source = SDL_GPUTransferBufferLocation {
+            transfer_buffer: source.get(),
+            offset: source_offset,
+        };
+        let destination = SDL_GPUBufferRegion {
+            buffer: destination.get(),
+            offset: destination_offset,
+            size,
+        };
+        unsafe {
+            SDL_UploadToGPUBuffer(self.inner, &source, &destination, cycle);
+        }
+    }
+
+    /// Copies all of `source` into `destination` at `destination_offset`,
+    /// with its rows tightly packed.
+    pub fn download_from_texture(
+        &mut self,
+        source: &SdlGpuTexture,
+        destination: &SdlGpuTransferBuffer,
+        destination_offset: u32,
+    ) {
+        let source = SDL_GPUTextureRegion {
+            texture: source.get(),
+            mip_level: 0,
+            layer: 0,
+            x: 0,
+            y: 0,
+            z: 0,
+            w: source.width(),
+            h: source.height(),
+            d: 1,
+        };
+        let destination = SDL_GPUTextureTransferInfo {
+            transfer_buffer: destination.get(),
+            offset: destination_offset,
+            pixels_per_row: 0,
+            rows_per_layer: 0,
+        };
+        unsafe {
+            SDL_DownloadFromGPUTexture(self.inner, &source, &destination);
+        }
+    }
+}
+
+impl Drop for CopyPass<'_, '_> {
+    fn drop(&mut self) {
+        unsafe {
+            SDL_EndGPUCopyPass(self.inner);
+        }
+    }
+}
+
+/// A render pass, ended when dropped.
+pub struct RenderPass<'c, 'a> {
+    inner: *mut SDL_GPURenderPass,
+    command_buffer: &'c mut CommandBuffer<'a>,
+}
+
+impl RenderPass<'_, '_> {
+    pub fn bind_graphics_pipeline(&mut self, pipeline: &SdlGpuGraphicsPipeline) {
+        unsafe {
+            SDL_BindGPUGraphicsPipeline(self.inner, pipeline.get());
+        }
+    }
+
+    pub fn set_viewport(&mut self, viewport: &SDL_GPUViewport) {
+        unsafe {
+            SDL_SetGPUViewport(self.inner, viewport);
+        }
+    }
+
+    pub fn bind_vertex_buffers(&mut self, first_slot: u32, buffers: &[(&SdlGpuBuffer, u32)]) {
+        let bindings: Vec<_> = buffers
+            .iter()
+            .map(|(buffer, offset)| SDL_GPUBufferBinding {
+                buffer: buffer.get(),
+                offset: *offset,
+            })
+            .collect();
+        unsafe {
+            SDL_BindGPUVertexBuffers(
+                self.inner,
+                first_slot,
+                bindings.as_ptr(),
+                bindings.len() as u32,
+            );
+        }
+    }
+
+    pub fn bind_index_buffer(
+        &mut self,
+        buffer: &SdlGpuBuffer,
+        offset: u32,
+        element_size: SDL_GPUIndexElementSize,
+    ) {
+        let binding = SDL_GPUBufferBinding {
+            buffer: buffer.get(),
+            offset,
+        };
+        unsafe {
+            SDL_BindGPUIndexBuffer(self.inner, &binding, element_size);
+        }
+    }
+
+    pub fn push_vertex_uniform_data<T: Pod>(&mut self, slot: u32, data: &T) {
+        self.command_buffer.push_vertex_uniform_data(slot, data);
+    }
+
+    pub fn draw_indexed_primitives(
+        &mut self,
+        num_indices: u32,
+        num_instances: u32,
+        first_index: u32,
+        vertex_offset: i32,
+        first_instance: u32,
+    ) {
+        unsafe {
+            SDL_DrawGPUIndexedPrimitives(
+                self.inner,
+                num_indices,
+                num_instances,
+                first_index,
+                vertex_offset,
+                first_instance,
+            );
+        }
+    }
+}
+
+impl Drop for RenderPass<'_, '_> {
+    fn drop(&mut self) {
+        unsafe {
+            SDL_EndGPURenderPass(self.inner);
+        }
+    }
+}
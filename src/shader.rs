@@ -0,0 +1,59 @@
+use std::ffi::CStr;
+
+use sdl3_sys::gpu::{SDL_GPUShaderFormat, SDL_GPUShaderStage};
+
+use crate::sdl::ShaderInfo;
+
+/// What SDL needs to know about a shader entry point besides its code.
+/// build.rs reflects this from the WGSL source, so it can't get out of sync
+/// with the shader's bindings.
+pub struct ShaderMetadata {
+    pub entrypoint: &'static CStr,
+    pub stage: SDL_GPUShaderStage,
+    pub num_samplers: u32,
+    pub num_storage_textures: u32,
+    pub num_storage_buffers: u32,
+    pub num_uniform_buffers: u32,
+}
+
+impl ShaderMetadata {
+    pub fn shader_info<'a>(&self, code: &'a [u8], format: SDL_GPUShaderFormat) -> ShaderInfo<'a> {
+        ShaderInfo {
+            code,
+            entrypoint: self.entrypoint,
+            format,
+            stage: self.stage,
+            num_samplers: self.num_samplers,
+            num_storage_textures: self.num_storage_textures,
+            num_storage_buffers: self.num_storage_buffers,
+            num_uniform_buffers: self.num_uniform_buffers,
+        }
+    }
+}
+
+/// A shader entry point compiled by build.rs to every format it could
+/// produce, so the GPU backend can be picked at runtime.
+pub struct EmbeddedShader {
+    /// The WGSL file it was compiled from, relative to the crate root.
+    #[cfg_attr(not(all(debug_assertions, feature = "hot-reload")), allow(dead_code))]
+    pub source: &'static str,
+    pub metadata: ShaderMetadata,
+    pub code: &'static [(SDL_GPUShaderFormat, &'static [u8])],
+}
+
+impl EmbeddedShader {
+    /// The formats this shader is available in.
+    pub fn formats(&self) -> SDL_GPUShaderFormat {
+        self.code
+            .iter()
+            .fold(0, |formats, (format, _)| formats | *format)
+    }
+
+    /// Picks the code for one of the formats the device can consume.
+    pub fn shader_info(&self, device_formats: SDL_GPUShaderFormat) -> Option<ShaderInfo<'static>> {
+        self.code
+            .iter()
+            .find(|(format, _)| device_formats & format != 0)
+            .map(|(format, code)| self.metadata.shader_info(code, *format))
+    }
+}
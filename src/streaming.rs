@@ -0,0 +1,173 @@
+use sdl3_sys::gpu::SDL_GPU_TRANSFERBUFFERUSAGE_UPLOAD;
+
+use crate::{
+    error::SdlResult,
+    sdl::{CopyPass, GpuDevice, SdlGpuBuffer, SdlGpuTransferBuffer},
+    util::{slice_as_bytes, Pod},
+};
+
+const ALIGNMENT: usize = 16;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RingAllocation {
+    pub offset: usize,
+    /// The allocation went back to the start of the ring, so earlier
+    /// allocations may still be in use by the GPU.
+    pub wrapped: bool,
+}
+
+/// Hands out consecutive regions of a fixed-size buffer, going back to the
+/// start once the end is reached.
+pub struct RingAllocator {
+    capacity: usize,
+    head: usize,
+}
+
+impl RingAllocator {
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, head: 0 }
+    }
+
+    pub fn allocate(&mut self, size: usize, alignment: usize) -> Option<RingAllocation> {
+        if size > self.capacity {
+            return None;
+        }
+        let start = self.head.next_multiple_of(alignment);
+        let allocation = if start + size > self.capacity {
+            RingAllocation {
+                offset: 0,
+                wrapped: true,
+            }
+        } else {
+            RingAllocation {
+                offset: start,
+                wrapped: false,
+            }
+        };
+        self.head = allocation.offset + size;
+        Some(allocation)
+    }
+}
+
+/// Streams data that changes every frame into GPU buffers without waiting for
+/// the GPU to finish with the previous contents.
+///
+/// Writes are packed one after another into a single transfer buffer. When
+/// the ring wraps around the transfer buffer is mapped with `cycle = true`, so
+/// SDL hands us fresh memory instead of the memory in-flight copies are still
+/// reading from. A write that's bigger than the whole ring replaces the
+/// transfer buffer with one it fits in.
+///
+/// Destination buffers aren't cycled, so the GPU has to be done with them
+/// before they're written again, e.g. by keeping one per frame slot.
+pub struct StreamingUploader<'a> {
+    device: &'a GpuDevice,
+    transfer_buffer: SdlGpuTransferBuffer<'a>,
+    ring: RingAllocator,
+}
+
+impl<'a> StreamingUploader<'a> {
+    pub fn new(device: &'a GpuDevice, capacity: u32) -> SdlResult<Self> {
+        let transfer_buffer =
+            device.create_transfer_buffer(SDL_GPU_TRANSFERBUFFERUSAGE_UPLOAD, capacity)?;
+        Ok(Self {
+            device,
+            transfer_buffer,
+            ring: RingAllocator::new(capacity as usize),
+        })
+    }
+
+    /// Replaces the start of `destination`, which has to be big enough, with
+    /// `data`.
+    pub fn write<T: Pod>(
+        &mut self,
+        copy_pass: &mut CopyPass,
+        data: &[T],
+        destination: &SdlGpuBuffer,
+    ) -> SdlResult<()> {
+        let bytes = slice_as_bytes(data);
+        assert!(bytes.len() <= destination.size() as usize);
+        let allocation = match self.ring.allocate(bytes.len(), ALIGNMENT) {
+            Some(allocation) => allocation,
+            None => self.grow(bytes.len())?,
+        };
+
+        {
+            let mut mapping = self.transfer_buffer.map(allocation.wrapped)?;
+            mapping[allocation.offset..allocation.offset + bytes.len()].copy_from_slice(bytes);
+        }
+        copy_pass.upload_to_buffer(
+            &self.transfer_buffer,
+            allocation.offset as u32,
+            destination,
+            0,
+            bytes.len() as u32,
+            false,
+        );
+        Ok(())
+    }
+
+    /// Replaces the transfer buffer with one that can hold `size` bytes, and
+    /// allocates them from its start. SDL only frees the old one once the
+    /// copies reading from it are done.
+    fn grow(&mut self, size: usize) -> SdlResult<RingAllocation> {
+        let capacity = size.next_power_of_two();
+        self.transfer_buffer = self
+            .device
+            .create_transfer_buffer(SDL_GPU_TRANSFERBUFFERUSAGE_UPLOAD, capacity as u32)?;
+        self.ring = RingAllocator::new(capacity);
+        Ok(self
+            .ring
+            .allocate(size, ALIGNMENT)
+            .expect("the new transfer buffer fits the write"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocations_are_consecutive_and_aligned() {
+        let mut ring = RingAllocator::new(256);
+        let offsets: Vec<_> = (0..3)
+            .map(|_| ring.allocate(20, 16).unwrap().offset)
+            .collect();
+        assert_eq!(offsets, [0, 32, 64]);
+    }
+
+    #[test]
+    fn wraps_when_the_end_is_reached() {
+        let mut ring = RingAllocator::new(100);
+        assert!(!ring.allocate(60, 4).unwrap().wrapped);
+        let allocation = ring.allocate(60, 4).unwrap();
+        assert_eq!(
+            allocation,
+            RingAllocation {
+                offset: 0,
+                wrapped: true
+            }
+        );
+        assert_eq!(
+            ring.allocate(40, 4).unwrap(),
+            RingAllocation {
+                offset: 60,
+                wrapped: false
+            }
+        );
+    }
+
+    #[test]
+    fn alignment_padding_can_cause_a_wrap() {
+        let mut ring = RingAllocator::new(64);
+        ring.allocate(33, 1).unwrap();
+        assert!(ring.allocate(16, 32).unwrap().wrapped);
+    }
+
+    #[test]
+    fn oversized_allocations_fail() {
+        let mut ring = RingAllocator::new(64);
+        assert_eq!(ring.allocate(65, 1), None);
+        assert_eq!(ring.allocate(64, 1).unwrap().offset, 0);
+    }
+}
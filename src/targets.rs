@@ -0,0 +1,302 @@
+use sdl3_sys::{
+    gpu::{
+        SDL_GPUCompareOp, SDL_GPUPresentMode, SDL_GPUSampleCount, SDL_GPUSwapchainComposition,
+        SDL_GPUTextureCreateInfo, SDL_GPUTextureFormat, SDL_GPUTextureUsageFlags,
+        SDL_GPU_COMPAREOP_LESS, SDL_GPU_LOADOP_CLEAR, SDL_GPU_LOADOP_DONT_CARE,
+        SDL_GPU_PRESENTMODE_IMMEDIATE, SDL_GPU_PRESENTMODE_MAILBOX, SDL_GPU_PRESENTMODE_VSYNC,
+        SDL_GPU_STOREOP_DONT_CARE, SDL_GPU_STOREOP_RESOLVE, SDL_GPU_STOREOP_STORE,
+        SDL_GPU_SWAPCHAINCOMPOSITION_HDR10_ST2048,
+        SDL_GPU_SWAPCHAINCOMPOSITION_HDR_EXTENDED_LINEAR, SDL_GPU_SWAPCHAINCOMPOSITION_SDR,
+        SDL_GPU_SWAPCHAINCOMPOSITION_SDR_LINEAR, SDL_GPU_TEXTUREFORMAT_D16_UNORM,
+        SDL_GPU_TEXTUREFORMAT_D32_FLOAT, SDL_GPU_TEXTURETYPE_2D, SDL_GPU_TEXTUREUSAGE_COLOR_TARGET,
+        SDL_GPU_TEXTUREUSAGE_DEPTH_STENCIL_TARGET,
+    },
+    pixels::SDL_FColor,
+};
+
+use crate::{
+    error::SdlResult,
+    sdl::{
+        ClaimedWindow, ColorTarget, DepthStencilTarget, GpuDevice, RenderTexture, SdlGpuTexture,
+    },
+};
+
+#[derive(Copy, Clone)]
+pub struct DepthSettings {
+    pub format: SDL_GPUTextureFormat,
+    pub compare_op: SDL_GPUCompareOp,
+}
+
+impl Default for DepthSettings {
+    fn default() -> Self {
+        Self {
+            format: SDL_GPU_TEXTUREFORMAT_D32_FLOAT,
+            compare_op: SDL_GPU_COMPAREOP_LESS,
+        }
+    }
+}
+
+impl DepthSettings {
+    /// Falls back to D16_UNORM, which SDL guarantees is always available, if
+    /// the device can't use the requested format as a depth target.
+    pub fn supported_by(self, device: &GpuDevice) -> Self {
+        let supported = device.texture_supports_format(
+            self.format,
+            SDL_GPU_TEXTURETYPE_2D,
+            SDL_GPU_TEXTUREUSAGE_DEPTH_STENCIL_TARGET,
+        );
+        if supported {
+            self
+        } else {
+            Self {
+                format: SDL_GPU_TEXTUREFORMAT_D16_UNORM,
+                ..self
+            }
+        }
+    }
+}
+
+/// Returns the highest sample count, up to `requested`, that the device
+/// supports for all of the given formats.
+pub fn supported_sample_count(
+    device: &GpuDevice,
+    formats: &[SDL_GPUTextureFormat],
+    requested: SDL_GPUSampleCount,
+) -> SDL_GPUSampleCount {
+    [
+        SDL_GPUSampleCount::_8,
+        SDL_GPUSampleCount::_4,
+        SDL_GPUSampleCount::_2,
+    ]
+    .into_iter()
+    .filter(|sample_count| *sample_count <= requested)
+    .find(|sample_count| {
+        formats
+            .iter()
+            .all(|format| device.texture_supports_sample_count(*format, *sample_count))
+    })
+    .unwrap_or(SDL_GPUSampleCount::_1)
+}
+
+/// The requested present mode, followed by what to try if it isn't
+/// supported, best first. Ends with VSYNC, which is always supported.
+fn present_mode_fallbacks(requested: SDL_GPUPresentMode) -> &'static [SDL_GPUPresentMode] {
+    match requested {
+        // Neither of these wait for the display, which is what matters when
+        // profiling
+        SDL_GPU_PRESENTMODE_IMMEDIATE => &[
+            SDL_GPU_PRESENTMODE_IMMEDIATE,
+            SDL_GPU_PRESENTMODE_MAILBOX,
+            SDL_GPU_PRESENTMODE_VSYNC,
+        ],
+        SDL_GPU_PRESENTMODE_MAILBOX => &[SDL_GPU_PRESENTMODE_MAILBOX, SDL_GPU_PRESENTMODE_VSYNC],
+        _ => &[SDL_GPU_PRESENTMODE_VSYNC],
+    }
+}
+
+/// The requested swapchain composition, followed by what to try if it isn't
+/// supported, best first. Ends with SDR, which is always supported.
+fn swapchain_composition_fallbacks(
+    requested: SDL_GPUSwapchainComposition,
+) -> &'static [SDL_GPUSwapchainComposition] {
+    match requested {
+        // Stay linear, if possible
+        SDL_GPU_SWAPCHAINCOMPOSITION_HDR_EXTENDED_LINEAR => &[
+            SDL_GPU_SWAPCHAINCOMPOSITION_HDR_EXTENDED_LINEAR,
+            SDL_GPU_SWAPCHAINCOMPOSITION_SDR_LINEAR,
+            SDL_GPU_SWAPCHAINCOMPOSITION_SDR,
+        ],
+        SDL_GPU_SWAPCHAINCOMPOSITION_HDR10_ST2048 => &[
+            SDL_GPU_SWAPCHAINCOMPOSITION_HDR10_ST2048,
+            SDL_GPU_SWAPCHAINCOMPOSITION_SDR,
+        ],
+        SDL_GPU_SWAPCHAINCOMPOSITION_SDR_LINEAR => &[
+            SDL_GPU_SWAPCHAINCOMPOSITION_SDR_LINEAR,
+            SDL_GPU_SWAPCHAINCOMPOSITION_SDR,
+        ],
+        _ => &[SDL_GPU_SWAPCHAINCOMPOSITION_SDR],
+    }
+}
+
+/// Returns the requested present mode if the window supports it, or else
+/// the closest one it does.
+pub fn supported_present_mode(
+    window: &ClaimedWindow,
+    requested: SDL_GPUPresentMode,
+) -> SDL_GPUPresentMode {
+    present_mode_fallbacks(requested)
+        .iter()
+        .copied()
+        .find(|present_mode| window.supports_present_mode(*present_mode))
+        .unwrap_or(SDL_GPU_PRESENTMODE_VSYNC)
+}
+
+/// Returns the requested swapchain composition if the window supports it,
+/// or else the closest one it does.
+pub fn supported_swapchain_composition(
+    window: &ClaimedWindow,
+    requested: SDL_GPUSwapchainComposition,
+) -> SDL_GPUSwapchainComposition {
+    swapchain_composition_fallbacks(requested)
+        .iter()
+        .copied()
+        .find(|composition| window.supports_swapchain_composition(*composition))
+        .unwrap_or(SDL_GPU_SWAPCHAINCOMPOSITION_SDR)
+}
+
+/// Render targets that need to be the same size as the swapchain: the depth
+/// buffer and, when multisampling, the color target that gets resolved into
+/// the swapchain texture.
+pub struct RenderTargets<'a> {
+    device: &'a GpuDevice,
+    color_format: SDL_GPUTextureFormat,
+    depth_format: SDL_GPUTextureFormat,
+    sample_count: SDL_GPUSampleCount,
+    color: Option<SdlGpuTexture<'a>>,
+    depth: Option<SdlGpuTexture<'a>>,
+}
+
+impl<'a> RenderTargets<'a> {
+    pub fn new(
+        device: &'a GpuDevice,
+        color_format: SDL_GPUTextureFormat,
+        depth_format: SDL_GPUTextureFormat,
+        sample_count: SDL_GPUSampleCount,
+    ) -> Self {
+        Self {
+            device,
+            color_format,
+            depth_format,
+            sample_count,
+            color: None,
+            depth: None,
+        }
+    }
+
+    fn create_target(
+        &self,
+        format: SDL_GPUTextureFormat,
+        usage: SDL_GPUTextureUsageFlags,
+        width: u32,
+        height: u32,
+    ) -> SdlResult<SdlGpuTexture<'a>> {
+        self.device.create_texture(&SDL_GPUTextureCreateInfo {
+            r#type: SDL_GPU_TEXTURETYPE_2D,
+            format,
+            usage,
+            width,
+            height,
+            layer_count_or_depth: 1,
+            num_levels: 1,
+            sample_count: self.sample_count,
+            props: 0,
+        })
+    }
+
+    /// Recreates the targets if they don't match the given size.
+    pub fn resize(&mut self, width: u32, height: u32) -> SdlResult<()> {
+        let up_to_date = self
+            .depth
+            .as_ref()
+            .is_some_and(|depth| depth.width() == width && depth.height() == height);
+        if up_to_date {
+            return Ok(());
+        }
+
+        // Free the old textures before creating their replacements
+        self.color = None;
+        self.depth = None;
+        if self.sample_count != SDL_GPUSampleCount::_1 {
+            self.color = Some(self.create_target(
+                self.color_format,
+                SDL_GPU_TEXTUREUSAGE_COLOR_TARGET,
+                width,
+                height,
+            )?);
+        }
+        self.depth = Some(self.create_target(
+            self.depth_format,
+            SDL_GPU_TEXTUREUSAGE_DEPTH_STENCIL_TARGET,
+            width,
+            height,
+        )?);
+        Ok(())
+    }
+
+    /// Describes rendering into `target`, either directly or by resolving
+    /// our multisampled color target into it.
+    pub fn color_target<'t>(
+        &'t self,
+        target: RenderTexture<'t>,
+        clear_color: SDL_FColor,
+    ) -> ColorTarget<'t> {
+        let (texture, store_op, resolve_texture) = match &self.color {
+            Some(color) => (
+                RenderTexture::Texture(color),
+                SDL_GPU_STOREOP_RESOLVE,
+                Some(target),
+            ),
+            None => (target, SDL_GPU_STOREOP_STORE, None),
+        };
+        ColorTarget {
+            texture,
+            clear_color,
+            load_op: SDL_GPU_LOADOP_CLEAR,
+            store_op,
+            resolve_texture,
+            // The multisampled contents are thrown away after the resolve,
+            // so it's fine to get a fresh texture if the old one is busy
+            cycle: self.color.is_some(),
+            cycle_resolve_texture: false,
+        }
+    }
+
+    pub fn depth_target(&self) -> DepthStencilTarget<'_> {
+        let depth = self
+            .depth
+            .as_ref()
+            .expect("render targets haven't been sized yet");
+        DepthStencilTarget {
+            texture: depth,
+            clear_depth: 1.0,
+            load_op: SDL_GPU_LOADOP_CLEAR,
+            store_op: SDL_GPU_STOREOP_DONT_CARE,
+            stencil_load_op: SDL_GPU_LOADOP_DONT_CARE,
+            stencil_store_op: SDL_GPU_STOREOP_DONT_CARE,
+            cycle: true,
+            clear_stencil: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::{PRESENT_MODES, SWAPCHAIN_COMPOSITIONS};
+
+    #[test]
+    fn present_modes_fall_back_to_vsync() {
+        for (name, present_mode) in PRESENT_MODES {
+            let fallbacks = present_mode_fallbacks(*present_mode);
+            assert!(fallbacks.first() == Some(present_mode), "{}", name);
+            assert!(
+                fallbacks.last() == Some(&SDL_GPU_PRESENTMODE_VSYNC),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn swapchain_compositions_fall_back_to_sdr() {
+        for (name, composition) in SWAPCHAIN_COMPOSITIONS {
+            let fallbacks = swapchain_composition_fallbacks(*composition);
+            assert!(fallbacks.first() == Some(composition), "{}", name);
+            assert!(
+                fallbacks.last() == Some(&SDL_GPU_SWAPCHAINCOMPOSITION_SDR),
+                "{}",
+                name
+            );
+        }
+    }
+}
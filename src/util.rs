@@ -1,7 +1,7 @@
 use std::str::Utf8Error;
 
 pub fn null_terminated_sdl_str<'a>(start: *const i8) -> Result<Option<&'a str>, Utf8Error> {
-    if start != std::ptr::null() {
+    if !start.is_null() {
         unsafe {
             let mut end = start;
             while *end != 0 {
@@ -9,9 +9,36 @@ pub fn null_terminated_sdl_str<'a>(start: *const i8) -> Result<Option<&'a str>,
             }
             let len = end.offset_from(start);
             let str_slice = std::slice::from_raw_parts(start as *const u8, len as usize);
-            std::str::from_utf8(str_slice).map(|x| Some(x))
+            std::str::from_utf8(str_slice).map(Some)
         }
     } else {
         Ok(None)
     }
 }
+
+/// Plain data that can be sent to the GPU as its raw bytes.
+///
+/// # Safety
+///
+/// Every byte of the type must be initialized, so it can't contain any
+/// padding.
+pub unsafe trait Pod: Copy {}
+
+unsafe impl Pod for u8 {}
+unsafe impl Pod for u16 {}
+unsafe impl Pod for u32 {}
+unsafe impl Pod for i32 {}
+unsafe impl Pod for f32 {}
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+unsafe impl Pod for glam::Mat4 {}
+
+/// Views a slice of plain data as its raw bytes.
+pub fn slice_as_bytes<T: Pod>(data: &[T]) -> &[u8] {
+    // Safety: Pod guarantees every byte is initialized
+    unsafe { std::slice::from_raw_parts(data.as_ptr() as *const u8, std::mem::size_of_val(data)) }
+}
+
+/// Views plain data as its raw bytes.
+pub fn as_bytes<T: Pod>(data: &T) -> &[u8] {
+    slice_as_bytes(std::slice::from_ref(data))
+}
@@ -0,0 +1,171 @@
+use sdl3_sys::gpu::{
+    SDL_GPUVertexAttribute, SDL_GPUVertexBufferDescription, SDL_GPUVertexElementFormat,
+    SDL_GPU_VERTEXELEMENTFORMAT_FLOAT, SDL_GPU_VERTEXELEMENTFORMAT_FLOAT2,
+    SDL_GPU_VERTEXELEMENTFORMAT_FLOAT3, SDL_GPU_VERTEXELEMENTFORMAT_FLOAT4,
+    SDL_GPU_VERTEXELEMENTFORMAT_INT, SDL_GPU_VERTEXELEMENTFORMAT_INT2,
+    SDL_GPU_VERTEXELEMENTFORMAT_INT3, SDL_GPU_VERTEXELEMENTFORMAT_INT4,
+    SDL_GPU_VERTEXELEMENTFORMAT_UBYTE4, SDL_GPU_VERTEXELEMENTFORMAT_UINT,
+    SDL_GPU_VERTEXELEMENTFORMAT_UINT2, SDL_GPU_VERTEXELEMENTFORMAT_UINT3,
+    SDL_GPU_VERTEXELEMENTFORMAT_UINT4, SDL_GPU_VERTEXINPUTRATE_VERTEX,
+};
+
+use crate::util::Pod;
+
+/// A type that can be used as a vertex attribute.
+pub trait VertexFormat: Pod {
+    const FORMAT: SDL_GPUVertexElementFormat;
+}
+
+macro_rules! vertex_format {
+    ($ty:ty, $format:ident) => {
+        impl VertexFormat for $ty {
+            const FORMAT: SDL_GPUVertexElementFormat = $format;
+        }
+    };
+}
+
+vertex_format!(f32, SDL_GPU_VERTEXELEMENTFORMAT_FLOAT);
+vertex_format!([f32; 2], SDL_GPU_VERTEXELEMENTFORMAT_FLOAT2);
+vertex_format!([f32; 3], SDL_GPU_VERTEXELEMENTFORMAT_FLOAT3);
+vertex_format!([f32; 4], SDL_GPU_VERTEXELEMENTFORMAT_FLOAT4);
+vertex_format!(i32, SDL_GPU_VERTEXELEMENTFORMAT_INT);
+vertex_format!([i32; 2], SDL_GPU_VERTEXELEMENTFORMAT_INT2);
+vertex_format!([i32; 3], SDL_GPU_VERTEXELEMENTFORMAT_INT3);
+vertex_format!([i32; 4], SDL_GPU_VERTEXELEMENTFORMAT_INT4);
+vertex_format!(u32, SDL_GPU_VERTEXELEMENTFORMAT_UINT);
+vertex_format!([u32; 2], SDL_GPU_VERTEXELEMENTFORMAT_UINT2);
+vertex_format!([u32; 3], SDL_GPU_VERTEXELEMENTFORMAT_UINT3);
+vertex_format!([u32; 4], SDL_GPU_VERTEXELEMENTFORMAT_UINT4);
+vertex_format!([u8; 4], SDL_GPU_VERTEXELEMENTFORMAT_UBYTE4);
+
+/// Describes how a vertex type is laid out in a vertex buffer. Implement it
+/// with `vertex_layout!` so the description can't drift from the struct.
+pub trait VertexLayout: Sized {
+    /// One attribute per field, in declaration order, starting at location 0.
+    fn attributes(buffer_slot: u32) -> Vec<SDL_GPUVertexAttribute>;
+
+    fn buffer_description(slot: u32) -> SDL_GPUVertexBufferDescription {
+        SDL_GPUVertexBufferDescription {
+            slot,
+            pitch: std::mem::size_of::<Self>() as u32,
+            input_rate: SDL_GPU_VERTEXINPUTRATE_VERTEX,
+            instance_step_rate: 0,
+        }
+    }
+}
+
+/// Defines a `#[repr(C)]` vertex struct along with its `VertexLayout`. Every
+/// field's type must implement `VertexFormat`.
+macro_rules! vertex_layout {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($field_vis:vis $field:ident: $field_ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[repr(C)]
+        $vis struct $name {
+            $($field_vis $field: $field_ty),*
+        }
+
+        // Every field is a VertexFormat, and so Pod, so the struct is too as
+        // long as there's no padding between or after them
+        const _: () = assert!(
+            std::mem::size_of::<$name>() == 0 $(+ std::mem::size_of::<$field_ty>())*,
+            "vertex types can't contain padding"
+        );
+        unsafe impl $crate::util::Pod for $name {}
+
+        impl $crate::vertex::VertexLayout for $name {
+            fn attributes(
+                buffer_slot: u32,
+            ) -> Vec<sdl3_sys::gpu::SDL_GPUVertexAttribute> {
+                let formats = [$(
+                    <$field_ty as $crate::vertex::VertexFormat>::FORMAT
+                ),*];
+                let offsets = [$(std::mem::offset_of!($name, $field)),*];
+                formats
+                    .into_iter()
+                    .zip(offsets)
+                    .enumerate()
+                    .map(|(location, (format, offset))| sdl3_sys::gpu::SDL_GPUVertexAttribute {
+                        location: location as u32,
+                        buffer_slot,
+                        format,
+                        offset: offset as u32,
+                    })
+                    .collect()
+            }
+        }
+    };
+}
+
+pub(crate) use vertex_layout;
+
+#[cfg(test)]
+mod tests {
+    use std::mem::offset_of;
+
+    use super::*;
+    use crate::Vertex;
+
+    vertex_layout! {
+        #[derive(Clone, Copy)]
+        struct MixedVertex {
+            position: [f32; 3],
+            color: [u8; 4],
+            uv: [f32; 2],
+        }
+    }
+
+    #[test]
+    fn vertex_attributes_match_the_struct() {
+        let attributes = Vertex::attributes(0);
+        let offsets: Vec<_> = attributes.iter().map(|a| a.offset as usize).collect();
+        assert_eq!(
+            offsets,
+            [offset_of!(Vertex, pos), offset_of!(Vertex, color)]
+        );
+        assert!(attributes
+            .iter()
+            .all(|a| a.format == SDL_GPU_VERTEXELEMENTFORMAT_FLOAT4));
+        assert_eq!(
+            Vertex::buffer_description(0).pitch as usize,
+            std::mem::size_of::<Vertex>()
+        );
+    }
+
+    #[test]
+    fn mixed_formats_get_sequential_locations() {
+        let attributes = MixedVertex::attributes(1);
+        let described: Vec<_> = attributes
+            .iter()
+            .map(|a| (a.location, a.buffer_slot, a.format.0, a.offset as usize))
+            .collect();
+        assert_eq!(
+            described,
+            [
+                (
+                    0,
+                    1,
+                    SDL_GPU_VERTEXELEMENTFORMAT_FLOAT3.0,
+                    offset_of!(MixedVertex, position)
+                ),
+                (
+                    1,
+                    1,
+                    SDL_GPU_VERTEXELEMENTFORMAT_UBYTE4.0,
+                    offset_of!(MixedVertex, color)
+                ),
+                (
+                    2,
+                    1,
+                    SDL_GPU_VERTEXELEMENTFORMAT_FLOAT2.0,
+                    offset_of!(MixedVertex, uv)
+                ),
+            ]
+        );
+        assert_eq!(MixedVertex::buffer_description(1).pitch, 24);
+    }
+}
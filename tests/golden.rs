@@ -0,0 +1,222 @@
+//! Renders the cube headless at fixed rotations and compares the results
+//! with the reference images in tests/golden.
+//!
+//! Run with UPDATE_GOLDEN=1 to replace the references with whatever renders
+//! now, after checking that it looks right. The tests are skipped when no GPU
+//! device can be created, not even a software one like lavapipe.
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+const WIDTH: u32 = 256;
+const HEIGHT: u32 = 256;
+/// How far apart a channel can be and still count as the same. Different
+/// drivers don't rasterize and blend identically.
+const CHANNEL_TOLERANCE: u8 = 8;
+/// How many pixels, as a fraction of the image, can be further apart than
+/// that, e.g. along the cube's edges.
+const MAX_MISMATCHED: f64 = 0.005;
+/// What sdl3cube exits with in headless mode when it can't create a GPU
+/// device. See src/headless.rs.
+const NO_DEVICE_EXIT_CODE: i32 = 3;
+
+/// An 8-bit RGB image.
+#[derive(Clone, Debug, PartialEq)]
+struct Image {
+    width: u32,
+    height: u32,
+    rgb: Vec<u8>,
+}
+
+impl Image {
+    fn read_ppm<P: AsRef<Path>>(path: P) -> Image {
+        let path = path.as_ref();
+        let data = std::fs::read(path)
+            .unwrap_or_else(|error| panic!("Couldn't read {}: {}", path.display(), error));
+        Self::parse_ppm(&data).unwrap_or_else(|| panic!("{} isn't a PPM", path.display()))
+    }
+
+    /// Parses a binary PPM, as written by sdl3cube. Comments aren't
+    /// supported.
+    fn parse_ppm(data: &[u8]) -> Option<Image> {
+        // The magic number, width, height and maximum value, each followed
+        // by a single whitespace character
+        let mut fields = Vec::new();
+        let mut start = 0;
+        for (index, byte) in data.iter().enumerate() {
+            if byte.is_ascii_whitespace() {
+                if index > start {
+                    fields.push(std::str::from_utf8(&data[start..index]).ok()?);
+                }
+                start = index + 1;
+                if fields.len() == 4 {
+                    break;
+                }
+            }
+        }
+        let [magic, width, height, max] = fields[..] else {
+            return None;
+        };
+        if magic != "P6" || max != "255" {
+            return None;
+        }
+        let width = width.parse().ok()?;
+        let height = height.parse().ok()?;
+        let rgb = data[start..].to_vec();
+        (rgb.len() == width as usize * height as usize * 3).then_some(Image { width, height, rgb })
+    }
+
+    fn write_ppm<P: AsRef<Path>>(&self, path: P) {
+        let mut data = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        data.extend_from_slice(&self.rgb);
+        std::fs::write(path, data).unwrap();
+    }
+}
+
+/// Returns how many pixels differ by more than the tolerance, and an image
+/// highlighting them in red over a dimmed copy of `actual`.
+fn compare(expected: &Image, actual: &Image) -> (usize, Image) {
+    assert_eq!(
+        (expected.width, expected.height),
+        (actual.width, actual.height),
+        "image sizes differ"
+    );
+    let mut mismatched = 0;
+    let mut diff = Vec::with_capacity(actual.rgb.len());
+    for (expected, actual) in expected.rgb.chunks_exact(3).zip(actual.rgb.chunks_exact(3)) {
+        let matches = expected
+            .iter()
+            .zip(actual)
+            .all(|(a, b)| a.abs_diff(*b) <= CHANNEL_TOLERANCE);
+        if matches {
+            diff.extend(actual.iter().map(|channel| channel / 4));
+        } else {
+            mismatched += 1;
+            diff.extend_from_slice(&[255, 0, 0]);
+        }
+    }
+    let diff = Image {
+        width: actual.width,
+        height: actual.height,
+        rgb: diff,
+    };
+    (mismatched, diff)
+}
+
+/// Renders one frame with the cube turned by `rotation` degrees, or returns
+/// None if there's no GPU to render with.
+fn render(rotation: f32) -> Option<Image> {
+    let output = PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+        .join("golden")
+        .join(format!("render_{}", rotation));
+    let result = Command::new(env!("CARGO_BIN_EXE_sdl3cube"))
+        .args(["--headless", "--frames", "1", "--image-format", "ppm"])
+        .args(["--size", &format!("{}x{}", WIDTH, HEIGHT)])
+        // Resolving multisampling is where drivers differ most
+        .args(["--msaa", "1"])
+        .args(["--rotation", &rotation.to_string()])
+        .arg("--output")
+        .arg(&output)
+        .output()
+        .expect("couldn't run sdl3cube");
+    if result.status.code() == Some(NO_DEVICE_EXIT_CODE) {
+        eprintln!(
+            "Skipping, no GPU device is available:\n{}",
+            String::from_utf8_lossy(&result.stderr)
+        );
+        return None;
+    }
+    assert!(
+        result.status.success(),
+        "sdl3cube failed with {}:\n{}{}",
+        result.status,
+        String::from_utf8_lossy(&result.stdout),
+        String::from_utf8_lossy(&result.stderr)
+    );
+    Some(Image::read_ppm(output.join("frame0000.ppm")))
+}
+
+fn check_golden(name: &str, rotation: f32) {
+    let Some(actual) = render(rotation) else {
+        return;
+    };
+    let reference_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.ppm", name));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(reference_path.parent().unwrap()).unwrap();
+        actual.write_ppm(&reference_path);
+        return;
+    }
+    if !reference_path.exists() {
+        panic!(
+            "{} doesn't exist. Run with UPDATE_GOLDEN=1 to create it.",
+            reference_path.display()
+        );
+    }
+
+    let expected = Image::read_ppm(&reference_path);
+    let (mismatched, diff) = compare(&expected, &actual);
+    let max_mismatched = (MAX_MISMATCHED * (actual.width * actual.height) as f64) as usize;
+    if mismatched > max_mismatched {
+        let failures = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("golden");
+        let actual_path = failures.join(format!("{}_actual.ppm", name));
+        let diff_path = failures.join(format!("{}_diff.ppm", name));
+        actual.write_ppm(&actual_path);
+        diff.write_ppm(&diff_path);
+        panic!(
+            "{} pixels differ from {}, more than the {} allowed.\nRendered: {}\nDiff: {}",
+            mismatched,
+            reference_path.display(),
+            max_mismatched,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+#[test]
+fn cube_facing_the_camera() {
+    check_golden("cube_0", 0.0);
+}
+
+#[test]
+fn cube_turned_30_degrees() {
+    check_golden("cube_30", 30.0);
+}
+
+#[test]
+fn cube_turned_45_degrees() {
+    check_golden("cube_45", 45.0);
+}
+
+#[test]
+fn comparison_allows_small_differences() {
+    let expected = Image {
+        width: 3,
+        height: 1,
+        rgb: vec![100, 100, 100, 0, 0, 0, 255, 255, 255],
+    };
+    let mut actual = expected.clone();
+    actual.rgb[0] += CHANNEL_TOLERANCE;
+    actual.rgb[5] = CHANNEL_TOLERANCE + 1;
+    let (mismatched, diff) = compare(&expected, &actual);
+    assert_eq!(mismatched, 1);
+    assert_eq!(diff.rgb[3..6], [255, 0, 0]);
+    assert_eq!(diff.rgb[6..], [63, 63, 63]);
+}
+
+#[test]
+fn ppm_round_trips() {
+    let image = Image {
+        width: 2,
+        height: 1,
+        rgb: vec![1, 2, 3, 4, 5, 6],
+    };
+    let data = b"P6\n2 1\n255\n\x01\x02\x03\x04\x05\x06";
+    assert_eq!(Image::parse_ppm(data), Some(image));
+    assert_eq!(Image::parse_ppm(b"P6\n2 1\n255\n\x01\x02"), None);
+    assert_eq!(Image::parse_ppm(b"P3\n1 1\n255\n1 2 3"), None);
+}